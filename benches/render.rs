@@ -0,0 +1,66 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use svg_maker::{
+    Parent,
+    element::Element,
+    shapes::{group::Group, svg::Svg},
+};
+
+/// a single path with many line and curve commands, like a detailed map outline
+fn large_path() -> Element<Svg> {
+    let mut path = Element::path().move_to(0, 0);
+    for i in 0..50_000 {
+        let x = i as f64 * 0.1;
+        path = path.line_path(x, (x * 0.37).sin() * 100.).cubic_bezier(
+            (x, 1.5),
+            (x + 0.05, 2.25),
+            (x + 0.1, 3.125),
+        );
+    }
+    Element::svg().push(path.close_path())
+}
+
+/// many small elements with styles, like the bars and points of a chart
+fn many_rects() -> Element<Svg> {
+    Element::svg().push_iter((0..20_000).map(|i| {
+        Element::rect(i % 200, i / 200, 0.9, 0.9)
+            .id(&format!("r{i}"))
+            .class("bar")
+            .stroke_width(0.25)
+            .style_kv("--value", i)
+    }))
+}
+
+/// deeply nested groups
+fn deep_groups() -> Element<Svg> {
+    fn nest(depth: u32) -> Element<Group> {
+        let group = Element::group()
+            .class("level")
+            .push(Element::circle(depth, depth, 1));
+        if depth == 0 {
+            group
+        } else {
+            group.push(nest(depth - 1))
+        }
+    }
+    Element::svg().push_iter((0..50).map(|_| nest(200)))
+}
+
+fn render(c: &mut Criterion) {
+    for (name, svg) in [
+        ("large_path", large_path()),
+        ("many_rects", many_rects()),
+        ("deep_groups", deep_groups()),
+    ] {
+        let mut group = c.benchmark_group(name);
+        group.bench_function("render", |b| b.iter(|| black_box(svg.render(None))));
+        group.bench_function("render_to", |b| {
+            b.iter(|| svg.render_to(std::io::sink(), None).unwrap())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
@@ -3,9 +3,10 @@
 use std::error::Error;
 
 use svg_maker::{
-    ChildOf, Parent,
+    Parent, Raw,
     color::{Color, Oklch},
     element::{Element, Transform},
+    font::{FontMetrics, measure_text},
     shapes::{group::Group, svg::Svg, text::Text},
     units::{AlignAspectRatio, MeetOrSlice, Percent, TextAnchor},
     visit::Visit,
@@ -180,20 +181,6 @@ pub enum LegendPos {
     None,
 }
 
-struct Size {
-    w: f64,
-    h: f64,
-}
-
-impl From<(f64, f64)> for Size {
-    fn from(value: (f64, f64)) -> Self {
-        Self {
-            w: value.0,
-            h: value.1,
-        }
-    }
-}
-
 struct BarchartValue {
     value: f64,
     label: String,
@@ -209,6 +196,9 @@ impl BarchartValue {
     }
 }
 
+/// font-size of the text in the css of the chart
+const TITLE_FONT_SIZE: f64 = 24.;
+
 fn barchart(
     title: &str,
     values: &[BarchartValue],
@@ -219,7 +209,8 @@ fn barchart(
     let availible_height = opts.height - (padding * 2.);
     let availible_width = opts.width - (padding * 2.);
 
-    let title_height = 20.;
+    let title_font = FontMetrics::builtin("Inter").ok_or("no built-in metrics for Inter")?;
+    let title_height = measure_text(title, title_font, TITLE_FONT_SIZE).height();
     let title_offset_y = padding;
     let bars_offset_y = padding + if opts.show_title { title_height } else { 0. };
     let bars_offset_x = padding;
@@ -283,7 +274,7 @@ fn barchart(
         )
     };
 
-    let title = generate_title("title", (100., 10.).into());
+    let title = generate_title(title, title_font);
     let bars = generate_bars(values, bars_width, bars_height);
 
     let s = Element::svg()
@@ -306,7 +297,7 @@ fn barchart(
                 .id("div")
                 .width(30)
                 .height(20)
-                .push("<div style=\"background: red\">test</div>".to_string()),
+                .push(Raw::new("<div style=\"background: red\">test</div>")),
         )
         .push_if(
             opts.show_title,
@@ -337,13 +328,14 @@ fn barchart(
     Ok(s)
 }
 
-fn generate_title(title: &str, size: Size) -> Element<Text> {
-    Element::text(Percent(50), 10)
+/// The title with the top of its text at y = 0
+fn generate_title(title: &str, font: &FontMetrics) -> Element<Text> {
+    Element::text(Percent(50), font.ascent(TITLE_FONT_SIZE))
         .id("title")
         .stroke_linejoin(svg_maker::style::LineJoin::Miter)
         .stroke_miterlimit(0.)
         .text_anchor(TextAnchor::Middle)
-        .push("My awsome chart")
+        .push(title.to_string())
 }
 
 fn generate_bars(values: &[BarchartValue], w: f64, h: f64) -> Element<Group> {
@@ -1,36 +1,94 @@
-use crate::{Options, Viewbox, visit::Visit};
+use std::{
+    collections::HashSet,
+    fmt::{self, Write},
+};
 
-pub struct Buffer {
+use crate::{
+    Options, Viewbox,
+    diagnostics::{Checks, Code, Severity, url_references},
+    units::Length,
+    visit::Visit,
+};
+
+/// size of the buffered output before it is written to the sink
+const FLUSH_SIZE: usize = 8 * 1024;
+
+pub struct Buffer<'a> {
     inner: String,
+    /// writer that receives the output when streaming, the buffer then only keeps the part that
+    /// isn't written yet
+    sink: Option<&'a mut dyn fmt::Write>,
+    sink_result: fmt::Result,
     tabs: u32,
     pub(crate) opts: Options,
-    pub(crate) warnings: Vec<String>,
+    /// set when the render is checked, see [`crate::diagnostics`]
+    pub(crate) checks: Option<Checks>,
+    /// referenced ids when unused definitions are removed
+    pub(crate) used_ids: Option<HashSet<String>>,
     pub(crate) viewbox: Viewbox,
+    /// end of the name of the tag that is currently written, where its attributes starts
+    attrs_start: usize,
 }
 
-impl Buffer {
+impl<'a> Buffer<'a> {
     pub fn with_capacity(cap: usize) -> Self {
         Self {
             inner: String::with_capacity(cap),
+            sink: None,
+            sink_result: Ok(()),
             opts: Options::default(),
             tabs: 0,
-            warnings: Vec::new(),
+            checks: None,
+            used_ids: None,
             viewbox: Viewbox::default(),
+            attrs_start: 0,
+        }
+    }
+
+    /// Creates a buffer that streams the output to the writer, see [`Buffer::flush`].
+    pub(crate) fn with_sink(sink: &'a mut dyn fmt::Write, opts: Options) -> Self {
+        let mut buffer = Self::with_capacity(FLUSH_SIZE * 2);
+        buffer.sink = Some(sink);
+        buffer.opts = opts;
+        buffer
+    }
+
+    /// Writes the buffered output to the sink and returns the first error of the sink.
+    pub(crate) fn flush(&mut self) -> fmt::Result {
+        if let Some(sink) = &mut self.sink
+            && self.sink_result.is_ok()
+        {
+            self.sink_result = sink.write_str(&self.inner);
+        }
+        self.inner.clear();
+        self.sink_result
+    }
+
+    /// Writes the output to the sink when enough is buffered. Only called between tags, the
+    /// attributes of the current tag must stay in the buffer to be checked and sorted.
+    fn flush_if_full(&mut self) {
+        if self.sink.is_some() && self.inner.len() >= FLUSH_SIZE {
+            // the error is kept and returned by the last flush
+            let _ = self.flush();
         }
     }
 
     /// pushes the start of a tag: "<tag .."
     pub fn push_tag(&mut self, tag: &str) {
+        self.flush_if_full();
         self.indent();
         self.inner.push('<');
         self.inner.push_str(tag);
+        self.attrs_start = self.inner.len();
         self.tabs += 1;
     }
 
     /// append the end marker of a tag ">"
     pub fn push_tag_end(&mut self) {
+        self.sort_attributes();
         self.inner.push('>');
         self.push_newline();
+        self.flush_if_full();
     }
 
     /// appends a closing tag like: "</tag>"
@@ -41,13 +99,16 @@ impl Buffer {
         self.inner.push_str(tag);
         self.inner.push('>');
         self.push_newline();
+        self.flush_if_full();
     }
 
     /// appends "/>"
     pub fn push_tag_self_close(&mut self) {
+        self.sort_attributes();
         self.inner.push_str("/>");
         self.push_newline();
         self.tabs = self.tabs.saturating_sub(1);
+        self.flush_if_full();
     }
 
     pub fn push_attr(&mut self, attr: &str, value: &impl Visit) {
@@ -64,16 +125,221 @@ impl Buffer {
         }
     }
 
+    /// Pushes an attribute that isn't modeled by the element kind, the value is escaped.
+    /// The attribute is skipped with a warning if the name isn't a valid xml name or if the tag
+    /// already has an attribute with the same name.
+    pub fn push_extra_attr(&mut self, attr: &str, value: &str) {
+        if !is_xml_name(attr) {
+            self.diagnostic(
+                Severity::Warning,
+                Code::InvalidAttributeName,
+                format_args!("skipped attribute with invalid name: {attr}"),
+            );
+            return;
+        }
+        if self.has_attr(attr) {
+            self.diagnostic(
+                Severity::Warning,
+                Code::DuplicateAttribute,
+                format_args!("skipped duplicated attribute: {attr}"),
+            );
+            return;
+        }
+        if self.checks.is_some() {
+            for target in url_references(value) {
+                self.reference(target);
+            }
+            if matches!(attr, "href" | "xlink:href")
+                && let Some(target) = value.strip_prefix('#')
+            {
+                self.reference(target);
+            }
+        }
+        self.inner.push(' ');
+        self.inner.push_str(attr);
+        self.inner.push_str("=\"");
+        self.push_escaped(value);
+        self.inner.push('"');
+    }
+
+    /// Returns true if the current tag already has the attribute
+    fn has_attr(&self, attr: &str) -> bool {
+        let attrs = &self.inner[self.attrs_start..];
+        attrs
+            .match_indices(attr)
+            .any(|(i, _)| attrs[..i].ends_with(' ') && attrs[i + attr.len()..].starts_with("=\""))
+    }
+
+    /// The attributes pushed to the current tag, unescaped
+    pub(crate) fn attributes(&self) -> Vec<(String, String)> {
+        let mut attributes = vec![];
+        let mut rest = &self.inner[self.attrs_start..];
+        while let Some((name, value)) = rest.split_once("=\"")
+            && let Some((value, after)) = value.split_once('"')
+        {
+            let value = value
+                .replace("&quot;", "\"")
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&amp;", "&");
+            attributes.push((name.trim().to_string(), value));
+            rest = after;
+        }
+        attributes
+    }
+
+    /// Pushes the string with the xml special characters escaped
+    pub fn push_escaped(&mut self, str: &str) {
+        for c in str.chars() {
+            match c {
+                '&' => self.inner.push_str("&amp;"),
+                '<' => self.inner.push_str("&lt;"),
+                '>' => self.inner.push_str("&gt;"),
+                '"' => self.inner.push_str("&quot;"),
+                c => self.inner.push(c),
+            }
+        }
+    }
+
+    /// Pushes text that can't contain entities, like the css of a `<style>`, the text is wrapped
+    /// in a CDATA section if it has xml special characters.
+    pub fn push_text(&mut self, str: &str) {
+        if !str.contains(['<', '&']) && !str.contains("]]>") {
+            self.inner.push_str(str);
+            return;
+        }
+        self.inner.push_str("<![CDATA[");
+        // a CDATA section can't contain its end marker, so it is split over two sections
+        for (i, part) in str.split("]]>").enumerate() {
+            if i > 0 {
+                self.inner.push_str("]]]]><![CDATA[>");
+            }
+            self.inner.push_str(part);
+        }
+        self.inner.push_str("]]>");
+    }
+
     pub fn push_attr_if(&mut self, _attr: &str, _value: &impl Visit, _pred: impl Fn() -> bool) {}
 
     pub fn push_str(&mut self, str: &str) {
         self.inner.push_str(str);
     }
 
+    /// Pushes formatted output without an intermediate string: `push_fmt(format_args!(..))`
+    pub fn push_fmt(&mut self, args: fmt::Arguments) {
+        // writing to a string can't fail
+        let _ = self.inner.write_fmt(args);
+    }
+
+    /// Pushes the number rounded to the precision of the options, in the shortest form that
+    /// parses back to the same value. Negative zero is written as `0`.
+    pub fn push_number(&mut self, n: f64) {
+        let n = match self.opts.precision {
+            Some(precision) => round(n, precision),
+            None => n,
+        };
+        // -0 == 0
+        let n = if n == 0. { 0. } else { n };
+        if !n.is_finite() {
+            self.non_finite(n);
+        }
+        let start = self.inner.len();
+        self.push_fmt(format_args!("{n}"));
+        self.minify_number(start, n);
+    }
+
+    /// Same as [`Buffer::push_number`], without precision the shortest f32 representation is
+    /// used since `0.1_f32 as f64` is `0.10000000149011612`.
+    pub fn push_f32(&mut self, n: f32) {
+        if self.opts.precision.is_some() {
+            return self.push_number(n as f64);
+        }
+        let n = if n == 0. { 0. } else { n };
+        if !n.is_finite() {
+            self.non_finite(n as f64);
+        }
+        let start = self.inner.len();
+        self.push_fmt(format_args!("{n}"));
+        self.minify_number(start, n as f64);
+    }
+
+    fn non_finite(&mut self, n: f64) {
+        self.diagnostic(
+            Severity::Error,
+            Code::NonFiniteNumber,
+            format_args!("{n} is not a valid number"),
+        );
+    }
+
+    /// Applies `Optimizations::minify_numbers` to the number written at `start`
+    fn minify_number(&mut self, start: usize, n: f64) {
+        if !self.opts.optimizations.minify_numbers {
+            return;
+        }
+        let number = &self.inner[start..];
+        if number.starts_with("0.") {
+            self.inner.remove(start);
+        } else if number.starts_with("-0.") {
+            self.inner.remove(start + 1);
+        }
+
+        if n != 0. && !(1e-2..1e3).contains(&n.abs()) {
+            let mut exp = ShortStr::default();
+            if write!(exp, "{n:e}").is_ok() && exp.len < self.inner.len() - start {
+                self.inner.truncate(start);
+                self.inner.push_str(exp.as_str());
+            }
+        }
+    }
+
     pub fn push(&mut self, c: char) {
         self.inner.push(c)
     }
 
+    pub(crate) fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Sorts the segments of the buffer that starts at the positions by the key, the last segment
+    /// ends at the end of the buffer.
+    pub(crate) fn sort_segments(&mut self, starts: &[usize], key: impl Fn(&str) -> &str) {
+        let Some(&first) = starts.first() else {
+            return;
+        };
+        let tail = self.inner.split_off(first);
+        let mut segments = starts
+            .iter()
+            .zip(starts.iter().skip(1).chain([&(first + tail.len())]))
+            .map(|(start, end)| &tail[start - first..end - first])
+            .collect::<Vec<_>>();
+        segments.sort_by_key(|s| key(s));
+        self.inner.extend(segments);
+    }
+
+    /// Sorts the attributes of the current tag by name if enabled in the options.
+    fn sort_attributes(&mut self) {
+        if !self.opts.sort_attributes {
+            return;
+        }
+        // attribute values are escaped, so every `"` is a delimiter: ` name="value"`
+        let mut starts = vec![];
+        let mut pos = self.attrs_start;
+        while pos < self.inner.len() {
+            let attr = &self.inner[pos..];
+            let Some(end) = attr
+                .strip_prefix(' ')
+                .and_then(|a| a.find("=\"").map(|i| i + 3))
+                .and_then(|value| attr[value..].find('"').map(|i| value + i + 1))
+            else {
+                // not written by the buffer, keep the order
+                return;
+            };
+            starts.push(pos);
+            pos += end;
+        }
+        self.sort_segments(&starts, |a| a.trim_start().split('=').next().unwrap_or(a));
+    }
+
     fn indent(&mut self) {
         if !self.opts.optimizations.remove_indent {
             let count = self.tabs;
@@ -95,6 +361,7 @@ impl Buffer {
         self.inner.push(' ');
     }
 
+    /// The rendered output, when streaming only the part that isn't written to the sink yet.
     pub fn str(&self) -> &str {
         &self.inner
     }
@@ -103,12 +370,208 @@ impl Buffer {
         self.inner.pop();
     }
 
-    pub fn push_warning(&mut self, warning: &str) {
-        self.warnings.push(format!(
-            "warning file: {}, line {}, {}",
-            file!(),
-            line!(),
-            warning
-        ));
+    /// Starts an element, used to locate the diagnostics of the element.
+    pub(crate) fn enter(&mut self, tag: &str, id: Option<&str>) {
+        if let Some(checks) = &mut self.checks {
+            checks.enter(tag, id);
+        }
+    }
+
+    pub(crate) fn leave(&mut self) {
+        if let Some(checks) = &mut self.checks {
+            checks.leave();
+        }
+    }
+
+    /// Reports a problem with the current element, ignored if the render isn't checked.
+    pub(crate) fn diagnostic(&mut self, severity: Severity, code: Code, message: fmt::Arguments) {
+        if let Some(checks) = &mut self.checks {
+            checks.push(severity, code, message.to_string());
+        }
+    }
+
+    /// Adds a reference to an id that must exist somewhere in the document
+    pub(crate) fn reference(&mut self, target: &str) {
+        if let Some(checks) = &mut self.checks {
+            checks.reference(target);
+        }
+    }
+
+    /// Reports a negative size, like the width of a rect
+    pub(crate) fn check_size(&mut self, attr: &str, size: &Length) {
+        if let Length::Px(px) = size
+            && px.0 < 0.
+        {
+            self.diagnostic(
+                Severity::Error,
+                Code::NegativeSize,
+                format_args!("{attr} can't be negative: {}", px.0),
+            );
+        }
+    }
+}
+
+/// Rounds to the number of decimals, the rounding is skipped for numbers that are too large to
+/// have decimals.
+fn round(n: f64, decimals: u8) -> f64 {
+    let scale = 10_f64.powi(decimals as i32);
+    let scaled = n * scale;
+    if scaled.abs() >= 1e15 {
+        return n;
+    }
+    scaled.round() / scale
+}
+
+/// Stack string for the exponent form of a number, longer output fails to write.
+#[derive(Default)]
+struct ShortStr {
+    buf: [u8; 32],
+    len: usize,
+}
+
+impl ShortStr {
+    fn as_str(&self) -> &str {
+        // only str are written into the buffer
+        std::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for ShortStr {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Allows `write!` into the buffer, the output isn't escaped.
+impl Write for Buffer<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.push_str(s);
+        Ok(())
+    }
+}
+
+/// Returns true if the name is a valid xml name, ie `data-value` or `xlink:href`.
+/// NOTE: non ascii characters are accepted without checking the ranges of the spec.
+pub(crate) fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    let start = |c: char| c.is_ascii_alphabetic() || c == '_' || c == ':' || !c.is_ascii();
+    start(first) && chars.all(|c| start(c) || c.is_ascii_digit() || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn extra_attributes() {
+        let mut buffer = Buffer::with_capacity(10);
+        buffer.push_tag("g");
+        buffer.push_attr("x", &"1");
+        buffer.push_extra_attr("data-label", r#"a "b" & <c>"#);
+        buffer.push_extra_attr("x", "2");
+        buffer.push_extra_attr("on load", "alert(1)");
+        assert_eq!(
+            buffer.str(),
+            r#"<g x="1" data-label="a &quot;b&quot; &amp; &lt;c&gt;""#
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        let mut buffer = Buffer::with_capacity(10);
+        buffer.push_escaped(r#"<a href="x">&</a>"#);
+        assert_eq!(buffer.str(), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+
+        let mut buffer = Buffer::with_capacity(10);
+        buffer.push_text("a > b");
+        buffer.push_text("a > b:not([x]]>)");
+        buffer.push_text("a && b");
+        assert_eq!(
+            buffer.str(),
+            "a > b<![CDATA[a > b:not([x]]]]><![CDATA[>)]]><![CDATA[a && b]]>"
+        );
+    }
+
+    #[test]
+    fn sort_attributes() {
+        let mut buffer = Buffer::with_capacity(10);
+        buffer.opts.sort_attributes = true;
+        buffer.push_tag("rect");
+        buffer.push_attr("y", &"1");
+        buffer.push_attr("x", &"a=\"b\"");
+        buffer.push_extra_attr("data-x", "2");
+        buffer.push_tag_self_close();
+        assert_eq!(
+            buffer.str(),
+            "<rect data-x=\"2\" x=\"a=&quot;b&quot;\" y=\"1\"/>\n"
+        );
+    }
+
+    #[test]
+    fn formatting() {
+        let mut buffer = Buffer::with_capacity(10);
+        buffer.push_number(1.);
+        buffer.push_space();
+        buffer.push_number(-0.25);
+        let x = 1_u8;
+        write!(buffer, " {x}-{}", x + 1).unwrap();
+        assert_eq!(buffer.str(), "1 -0.25 1-2");
+    }
+
+    #[rstest]
+    #[case(Some(3), false, 0.1 + 0.2, "0.3")]
+    #[case(None, false, 0.1 + 0.2, "0.30000000000000004")]
+    #[case(Some(3), false, 1.23456, "1.235")]
+    #[case(Some(0), false, 12.5, "13")]
+    #[case(Some(3), false, -0.0001, "0")]
+    #[case(Some(3), true, 0.5, ".5")]
+    #[case(Some(3), true, -0.5, "-.5")]
+    #[case(Some(3), true, 1000., "1e3")]
+    #[case(Some(3), true, 1500., "1500")]
+    #[case(Some(3), true, 100., "100")]
+    #[case(None, true, 0.0001, "1e-4")]
+    #[case(None, true, -0.000012, "-1.2e-5")]
+    #[case(None, true, 0., "0")]
+    fn numbers(
+        #[case] precision: Option<u8>,
+        #[case] minify: bool,
+        #[case] n: f64,
+        #[case] expected: &str,
+    ) {
+        let mut buffer = Buffer::with_capacity(10);
+        buffer.opts.precision = precision;
+        buffer.opts.optimizations.minify_numbers = minify;
+        buffer.push_number(n);
+        assert_eq!(buffer.str(), expected);
+    }
+
+    #[test]
+    fn f32_numbers() {
+        let mut buffer = Buffer::with_capacity(10);
+        buffer.opts.precision = None;
+        buffer.push_f32(0.1);
+        buffer.push_space();
+        buffer.push_f32(-0.);
+        assert_eq!(buffer.str(), "0.1 0");
+    }
+
+    #[test]
+    fn xml_names() {
+        assert!(is_xml_name("data-x.y_z"));
+        assert!(is_xml_name("xlink:href"));
+        assert!(!is_xml_name(""));
+        assert!(!is_xml_name("1x"));
+        assert!(!is_xml_name("x=\"1\""));
     }
 }
@@ -0,0 +1,442 @@
+// ===== Diagnostics ============================================================
+//
+// Problems found while an element tree is rendered with `Element::render_checked`. The checks
+// runs as part of the render, every `Visit` impl can report what it knows is wrong with the
+// values it writes, and the document wide checks (duplicate ids, unresolved references) are
+// done when the render is finished.
+
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The document renders, but likely not as intended
+    Warning,
+    /// The document is invalid, viewers may not render the element
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    PathWithoutMoveTo,
+    UseWithoutHref,
+    NegativeSize,
+    DuplicateId,
+    UnresolvedReference,
+    NonFiniteNumber,
+    InvalidAttributeName,
+    DuplicateAttribute,
+    UnusedDefinition,
+    UnknownColor,
+}
+
+impl Code {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Code::PathWithoutMoveTo => "path-without-move-to",
+            Code::UseWithoutHref => "use-without-href",
+            Code::NegativeSize => "negative-size",
+            Code::DuplicateId => "duplicate-id",
+            Code::UnresolvedReference => "unresolved-reference",
+            Code::NonFiniteNumber => "non-finite-number",
+            Code::InvalidAttributeName => "invalid-attribute-name",
+            Code::DuplicateAttribute => "duplicate-attribute",
+            Code::UnusedDefinition => "unused-definition",
+            Code::UnknownColor => "unknown-color",
+        }
+    }
+
+    /// Returns true if the code is about ids and the references between elements
+    pub fn is_reference(&self) -> bool {
+        matches!(
+            self,
+            Code::DuplicateId | Code::UnresolvedReference | Code::UnusedDefinition
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Code,
+    /// Location of the element like `svg > g:nth-child(2) > rect:nth-child(1)`, the position
+    /// counts the element children of the parent.
+    pub path: String,
+    /// Id of the element
+    pub id: Option<String>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}[{}] {}", self.code.as_str(), self.path)?;
+        if let Some(id) = &self.id {
+            write!(f, " #{id}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Output of [`Element::render_checked`](crate::element::Element::render_checked)
+#[derive(Debug)]
+pub struct Rendered {
+    pub output: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Rendered {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+}
+
+struct Location {
+    tag: String,
+    id: Option<String>,
+    /// position among the element children of the parent, 0 for the root
+    position: usize,
+    /// number of element children visited so far
+    children: usize,
+}
+
+/// A reference to an id, resolved when the render is finished
+struct Reference {
+    target: String,
+    path: String,
+    id: Option<String>,
+    /// index of the definition that holds the reference
+    from: Option<usize>,
+}
+
+/// A child of a `<defs>` element
+struct Definition {
+    id: Option<String>,
+    path: String,
+}
+
+/// State of the checks while the tree is rendered, see [`Buffer::enter`](crate::buffer::Buffer).
+#[derive(Default)]
+pub(crate) struct Checks {
+    locations: Vec<Location>,
+    ids: HashSet<String>,
+    references: Vec<Reference>,
+    definitions: Vec<Definition>,
+    /// depth of the `<defs>` element that is visited
+    defs_depth: Option<usize>,
+    /// index of the definition that is visited
+    definition: Option<usize>,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+}
+
+impl Checks {
+    pub(crate) fn enter(&mut self, tag: &str, id: Option<&str>) {
+        let position = match self.locations.last_mut() {
+            Some(parent) => {
+                parent.children += 1;
+                parent.children
+            }
+            None => 0,
+        };
+        self.locations.push(Location {
+            tag: tag.to_string(),
+            id: id.map(str::to_string),
+            position,
+            children: 0,
+        });
+        let depth = self.locations.len();
+        match self.defs_depth {
+            Some(defs) if depth == defs + 1 => {
+                self.definition = Some(self.definitions.len());
+                self.definitions.push(Definition {
+                    id: id.map(str::to_string),
+                    path: self.path(),
+                });
+            }
+            None if tag == "defs" => self.defs_depth = Some(depth),
+            _ => {}
+        }
+        if let Some(id) = id
+            && !self.ids.insert(id.to_string())
+        {
+            self.push(
+                Severity::Error,
+                Code::DuplicateId,
+                format!("the id {id} is used by an earlier element"),
+            );
+        }
+    }
+
+    pub(crate) fn leave(&mut self) {
+        let depth = self.locations.len();
+        match self.defs_depth {
+            Some(defs) if depth == defs => self.defs_depth = None,
+            Some(defs) if depth == defs + 1 => self.definition = None,
+            _ => {}
+        }
+        self.locations.pop();
+    }
+
+    pub(crate) fn push(&mut self, severity: Severity, code: Code, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            code,
+            path: self.path(),
+            id: self.id(),
+            message,
+        });
+    }
+
+    /// Adds a reference to an id like `url(#id)` or `href="#id"`, the `#` is optional.
+    pub(crate) fn reference(&mut self, target: &str) {
+        self.references.push(Reference {
+            target: target.trim_start_matches('#').to_string(),
+            path: self.path(),
+            id: self.id(),
+            from: self.definition,
+        });
+    }
+
+    /// Returns the ids that are referenced, references made by definitions that aren't used
+    /// themselves are skipped.
+    pub(crate) fn used_ids(&self) -> HashSet<String> {
+        let mut used = HashSet::new();
+        let mut reached = vec![false; self.definitions.len()];
+        let mut targets = self
+            .references
+            .iter()
+            .filter(|r| r.from.is_none())
+            .map(|r| r.target.as_str())
+            .collect::<Vec<_>>();
+        while let Some(target) = targets.pop() {
+            if !used.insert(target.to_string()) {
+                continue;
+            }
+            let definition = self
+                .definitions
+                .iter()
+                .position(|d| d.id.as_deref() == Some(target));
+            if let Some(i) = definition
+                && !reached[i]
+            {
+                reached[i] = true;
+                targets.extend(
+                    self.references
+                        .iter()
+                        .filter(|r| r.from == Some(i))
+                        .map(|r| r.target.as_str()),
+                );
+            }
+        }
+        used
+    }
+
+    /// Resolves the references against the ids of the whole document and returns the
+    /// diagnostics in the order they were found.
+    pub(crate) fn finish(mut self) -> Vec<Diagnostic> {
+        let used = self.used_ids();
+        for definition in &self.definitions {
+            let message = match &definition.id {
+                Some(id) if used.contains(id) => continue,
+                Some(_) => "the definition is never referenced",
+                None => "a definition without an id can't be referenced",
+            };
+            self.diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                code: Code::UnusedDefinition,
+                path: definition.path.clone(),
+                id: definition.id.clone(),
+                message: message.to_string(),
+            });
+        }
+        for reference in self.references {
+            if !self.ids.contains(&reference.target) {
+                self.diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    code: Code::UnresolvedReference,
+                    path: reference.path,
+                    id: reference.id,
+                    message: format!("no element has the id {}", reference.target),
+                });
+            }
+        }
+        self.diagnostics
+    }
+
+    fn path(&self) -> String {
+        let mut path = String::new();
+        for location in &self.locations {
+            if !path.is_empty() {
+                path.push_str(" > ");
+            }
+            path.push_str(&location.tag);
+            if location.position > 0 {
+                let _ = write!(path, ":nth-child({})", location.position);
+            }
+        }
+        path
+    }
+
+    fn id(&self) -> Option<String> {
+        self.locations.last().and_then(|l| l.id.clone())
+    }
+}
+
+/// Returns the ids of the `url(#id)` references in an attribute or style value
+pub(crate) fn url_references(value: &str) -> impl Iterator<Item = &str> {
+    value.split("url(").skip(1).filter_map(|rest| {
+        let target = rest.split(')').next()?.trim().trim_matches(['"', '\'']);
+        target.strip_prefix('#')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Options, Parent,
+        color::Color,
+        element::Element,
+        shapes::path::{Command, Path},
+    };
+
+    use super::*;
+
+    #[test]
+    fn paths_and_references() {
+        let mut checks = Checks::default();
+        checks.enter("svg", None);
+        checks.enter("rect", Some("a"));
+        checks.leave();
+        checks.enter("g", None);
+        checks.enter("rect", Some("a"));
+        checks.reference("#b");
+        checks.reference("a");
+        checks.leave();
+        checks.leave();
+        checks.leave();
+
+        let diagnostics = checks.finish();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error[duplicate-id] svg > g:nth-child(2) > rect:nth-child(1) #a: the id a is used by an earlier element"
+        );
+        assert_eq!(diagnostics[1].code, Code::UnresolvedReference);
+        assert_eq!(diagnostics[1].message, "no element has the id b");
+    }
+
+    #[test]
+    fn render_checked() {
+        let svg = Element::svg()
+            .def(Element::circle(0, 0, 5).id("dot"))
+            .push(Element::rect(0, 0, 10, 10).fill(Color::Url("dot".to_string())))
+            .push(
+                Element::group()
+                    .push(Element::new(Path {
+                        path: vec![Command::Line((1, 1).into())],
+                    }))
+                    .push(Element::use_href(0, 0))
+                    .push(Element::circle(f64::NAN, 0, 1).id("dot")),
+            )
+            .push(Element::rect(0, 0, 1, 1).style_kv("clip-path", "url(#clip)"));
+        let rendered = svg.render_checked(None);
+        assert!(rendered.has_errors());
+        assert_eq!(rendered.output, svg.render(None));
+
+        let diagnostics = rendered
+            .diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                "error[path-without-move-to] svg > g:nth-child(3) > path:nth-child(1): path must start with a MoveTo command",
+                "warning[use-without-href] svg > g:nth-child(3) > use:nth-child(2): use without a href renders nothing",
+                "error[duplicate-id] svg > g:nth-child(3) > circle:nth-child(3) #dot: the id dot is used by an earlier element",
+                "error[non-finite-number] svg > g:nth-child(3) > circle:nth-child(3) #dot: NaN is not a valid number",
+                "error[unresolved-reference] svg > rect:nth-child(4): no element has the id clip",
+            ]
+        );
+    }
+
+    #[test]
+    fn unused_definitions() {
+        let svg = Element::svg()
+            .def(Element::circle(0, 0, 1).id("dot"))
+            .def(
+                Element::generic("linearGradient")
+                    .id("fade")
+                    .attr("href", "#base"),
+            )
+            .def(Element::generic("linearGradient").id("base"))
+            .def(Element::generic("linearGradient").id("unused"))
+            .def(Element::generic("clipPath"))
+            .push(Element::rect(0, 0, 1, 1).fill(Color::Url("fade".to_string())))
+            .push(Element::generic("g").attr("clip-path", "url(#missing)"));
+        let diagnostics = svg
+            .check_references()
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                "warning[unused-definition] svg > defs:nth-child(1) > circle:nth-child(1) #dot: the definition is never referenced",
+                "warning[unused-definition] svg > defs:nth-child(1) > linearGradient:nth-child(4) #unused: the definition is never referenced",
+                "warning[unused-definition] svg > defs:nth-child(1) > clipPath:nth-child(5): a definition without an id can't be referenced",
+                "error[unresolved-reference] svg > g:nth-child(3): no element has the id missing",
+            ]
+        );
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_unused_defs = true;
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        let svg = svg.render(Some(opts));
+        assert!(svg.contains(
+            r##"<defs><linearGradient id="fade" href="#base"/><linearGradient id="base"/></defs>"##
+        ));
+    }
+
+    #[test]
+    fn css_references() {
+        let svg = Element::svg()
+            .css("rect { fill: url(#fade) } circle { mask: url('#missing') }")
+            .def(Element::generic("linearGradient").id("fade"))
+            .def(Element::generic("linearGradient").id("unused"))
+            .push(Element::rect(0, 0, 1, 1));
+        let diagnostics = svg
+            .check_references()
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                "warning[unused-definition] svg > defs:nth-child(2) > linearGradient:nth-child(2) #unused: the definition is never referenced",
+                "error[unresolved-reference] svg > style:nth-child(1): no element has the id missing",
+            ]
+        );
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_unused_defs = true;
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        let svg = svg.render(Some(opts));
+        assert!(svg.contains(r#"<defs><linearGradient id="fade"/></defs>"#));
+    }
+
+    #[test]
+    fn url_references_in_values() {
+        let refs = url_references(r##"url(#a) url( "#b" ) url(http://x/y.svg#c)"##);
+        assert_eq!(refs.collect::<Vec<_>>(), ["a", "b"]);
+    }
+}
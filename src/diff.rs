@@ -0,0 +1,720 @@
+// ===== Diff ===================================================================
+//
+// Structural comparison of two element trees. The trees are rendered without formatting and
+// compared as xml, so every attribute of every kind is compared without the kinds exposing
+// them. Elements with an id are matched by id anywhere in the tree, the elements without an id
+// are matched in order with the siblings that have the same tag.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use roxmltree::{Document, Node};
+
+use crate::{Options, element::Element, marker_traits::ElementKind, visit::Visit};
+
+/// Numbers that differ less than this are equal, see [`diff_with_tolerance`]
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// The changes from one tree to another, the `Display` impl is a report with one line per
+/// change.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diff {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Location of the element like `svg > g:nth-child(2) > rect:nth-child(1)`, in the new tree
+    /// except for removed elements.
+    pub path: String,
+    pub id: Option<String>,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// The element has a new parent or changed its order with its siblings, `from` is the path
+    /// in the old tree
+    Moved {
+        from: String,
+    },
+    Attribute {
+        name: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// A property of the style attribute
+    Style {
+        property: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Text {
+        old: String,
+        new: String,
+    },
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Moved { .. } => '>',
+            _ => '~',
+        };
+        write!(f, "{sign} {}", self.path)?;
+        if let Some(id) = &self.id {
+            write!(f, " #{id}")?;
+        }
+        let value = |v: &Option<String>| match v {
+            Some(v) => format!("{v:?}"),
+            None => "(none)".to_string(),
+        };
+        match &self.kind {
+            ChangeKind::Added | ChangeKind::Removed => Ok(()),
+            ChangeKind::Moved { from } => write!(f, " (from {from})"),
+            ChangeKind::Attribute { name, old, new } => {
+                write!(f, ": {name} {} -> {}", value(old), value(new))
+            }
+            ChangeKind::Style { property, old, new } => {
+                write!(f, ": style {property} {} -> {}", value(old), value(new))
+            }
+            ChangeKind::Text { old, new } => write!(f, ": text {old:?} -> {new:?}"),
+        }
+    }
+}
+
+/// Compares two trees, see [`diff_with_tolerance`].
+/// ```
+/// # use svg_maker::{Parent, element::Element};
+/// let old = Element::svg().push(Element::rect(0, 0, 10, 10).id("bar"));
+/// let new = Element::svg()
+///     .push(Element::rect(0, 0, 10, 12).id("bar"))
+///     .push(Element::circle(5, 5, 1));
+/// let diff = svg_maker::diff(&old, &new);
+/// assert_eq!(
+///     diff.to_string(),
+///     "~ svg > rect:nth-child(1) #bar: height \"10\" -> \"12\"\n+ svg > circle:nth-child(2)\n"
+/// );
+/// ```
+pub fn diff<A, B>(old: &Element<A>, new: &Element<B>) -> Diff
+where
+    A: ElementKind + Visit,
+    B: ElementKind + Visit,
+{
+    diff_with_tolerance(old, new, DEFAULT_TOLERANCE)
+}
+
+/// Compares two trees, numbers in attribute and style values that differ less than the
+/// tolerance are equal. Elements with an id are matched by id, a changed id is reported as a
+/// removed and an added element. Elements without an id are matched by their position among
+/// the siblings with the same tag.
+///
+/// NOTE: raw markup that isn't well-formed xml can't be compared structurally, the rendered
+/// trees are then reported as a text change of the root.
+pub fn diff_with_tolerance<A, B>(old: &Element<A>, new: &Element<B>, tolerance: f64) -> Diff
+where
+    A: ElementKind + Visit,
+    B: ElementKind + Visit,
+{
+    let opts = || {
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        opts.precision = None;
+        opts
+    };
+    diff_rendered(
+        &old.render(Some(opts())),
+        &new.render(Some(opts())),
+        tolerance,
+    )
+}
+
+/// Compares two rendered trees, the whitespace between elements is ignored.
+pub(crate) fn diff_rendered(old_svg: &str, new_svg: &str, tolerance: f64) -> Diff {
+    let mut diff = Diff::default();
+    let (Ok(old_doc), Ok(new_doc)) = (Document::parse(old_svg), Document::parse(new_svg)) else {
+        if old_svg != new_svg {
+            let root = new_svg.trim_start().trim_start_matches('<');
+            let tag = root
+                .split(['>', '/', ' ', '\n', '\t'])
+                .next()
+                .unwrap_or_default();
+            diff.changes.push(Change {
+                path: tag.to_string(),
+                id: None,
+                kind: ChangeKind::Text {
+                    old: old_svg.to_string(),
+                    new: new_svg.to_string(),
+                },
+            });
+        }
+        return diff;
+    };
+
+    let (old_root, new_root) = (old_doc.root_element(), new_doc.root_element());
+    let mut differ = Differ {
+        tolerance,
+        old_ids: paths_by_id(old_root),
+        new_ids: paths_by_id(new_root),
+        diff,
+    };
+    let path = old_root.tag_name().name().to_string();
+    if old_root.tag_name() == new_root.tag_name() {
+        differ.element(old_root, new_root, &path);
+    } else {
+        differ.push(&path, old_root, ChangeKind::Removed);
+        differ.push(new_root.tag_name().name(), new_root, ChangeKind::Added);
+    }
+    differ.diff
+}
+
+struct Differ<'a, 'input> {
+    tolerance: f64,
+    /// the element and the path of every id
+    old_ids: HashMap<&'a str, (Node<'a, 'input>, String)>,
+    new_ids: HashMap<&'a str, (Node<'a, 'input>, String)>,
+    diff: Diff,
+}
+
+impl<'a, 'input> Differ<'a, 'input> {
+    /// Compares two matched elements and their children, `path` is in the new tree
+    fn element(&mut self, old: Node<'a, 'input>, new: Node<'a, 'input>, path: &str) {
+        self.attributes(old, new, path);
+        let (old_text, new_text) = (text(old), text(new));
+        if old_text != new_text {
+            self.push(
+                path,
+                new,
+                ChangeKind::Text {
+                    old: old_text,
+                    new: new_text,
+                },
+            );
+        }
+        self.children(old, new, path);
+    }
+
+    fn attributes(&mut self, old: Node, new: Node, path: &str) {
+        let names = old
+            .attributes()
+            .chain(new.attributes())
+            .map(|a| a.name())
+            .filter(|name| *name != "style")
+            .collect::<Vec<_>>();
+        let mut seen = HashSet::new();
+        for name in names {
+            if !seen.insert(name) {
+                continue;
+            }
+            let (a, b) = (old.attribute(name), new.attribute(name));
+            if !self.equal(a, b, name == "d") {
+                let kind = ChangeKind::Attribute {
+                    name: name.to_string(),
+                    old: a.map(str::to_string),
+                    new: b.map(str::to_string),
+                };
+                self.push(path, new, kind);
+            }
+        }
+
+        let (old_style, new_style) = (style(old), style(new));
+        let mut seen = HashSet::new();
+        for (name, _) in old_style.iter().chain(&new_style) {
+            if !seen.insert(*name) {
+                continue;
+            }
+            let (a, b) = (property(&old_style, name), property(&new_style, name));
+            if !self.equal(a, b, false) {
+                let kind = ChangeKind::Style {
+                    property: name.to_string(),
+                    old: a.map(str::to_string),
+                    new: b.map(str::to_string),
+                };
+                self.push(path, new, kind);
+            }
+        }
+    }
+
+    fn children(&mut self, old: Node<'a, 'input>, new: Node<'a, 'input>, path: &str) {
+        let old_children = old.children().filter(Node::is_element).collect::<Vec<_>>();
+        let new_children = new.children().filter(Node::is_element).collect::<Vec<_>>();
+        let child_path = |parent: &str, node: Node, position: usize| {
+            format!(
+                "{parent} > {}:nth-child({position})",
+                node.tag_name().name()
+            )
+        };
+
+        // matched by id among the siblings, or moved from anywhere in the old tree
+        let mut matched = vec![None; new_children.len()];
+        let mut moved = vec![false; new_children.len()];
+        let mut old_matched = vec![false; old_children.len()];
+        for (i, child) in new_children.iter().enumerate() {
+            let Some(id) = child.attribute("id") else {
+                continue;
+            };
+            if let Some(j) = old_children
+                .iter()
+                .position(|c| c.attribute("id") == Some(id))
+            {
+                matched[i] = Some(old_children[j]);
+                old_matched[j] = true;
+            } else if let Some((old_child, from)) = self.old_ids.get(id).cloned() {
+                let path = child_path(path, *child, i + 1);
+                self.push(&path, *child, ChangeKind::Moved { from });
+                self.element(old_child, *child, &path);
+                matched[i] = Some(old_child);
+                moved[i] = true;
+            }
+        }
+        // elements that moved to another parent are reported there
+        for (j, child) in old_children.iter().enumerate() {
+            if child
+                .attribute("id")
+                .is_some_and(|id| self.new_ids.contains_key(id))
+            {
+                old_matched[j] = true;
+            }
+        }
+        // the elements without an id in order by tag
+        let mut next = 0;
+        for (i, child) in new_children.iter().enumerate() {
+            if child.has_attribute("id") {
+                continue;
+            }
+            let found = (next..old_children.len()).find(|&j| {
+                let old_child = old_children[j];
+                !old_child.has_attribute("id") && old_child.tag_name() == child.tag_name()
+            });
+            if let Some(j) = found {
+                old_matched[j] = true;
+                next = j + 1;
+                matched[i] = Some(old_children[j]);
+            }
+        }
+
+        // siblings that changed their order, the longest run that is still in the old order
+        // stays in place and the others are moved
+        let old_positions = matched
+            .iter()
+            .zip(&moved)
+            .map(|(old_child, moved)| match old_child {
+                Some(old_child) if !moved => old_children.iter().position(|c| c == old_child),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let in_order = longest_increasing(&old_positions);
+
+        let old_path = self.old_path(old);
+        for (i, child) in new_children.iter().enumerate() {
+            let reordered = old_positions[i]
+                .filter(|_| !in_order[i])
+                .map(|j| child_path(&old_path, old_children[j], j + 1));
+            let child_path = child_path(path, *child, i + 1);
+            match matched[i] {
+                // moves are compared when they are found
+                Some(_) if moved[i] => {}
+                Some(old_child) => {
+                    if let Some(from) = reordered {
+                        self.push(&child_path, *child, ChangeKind::Moved { from });
+                    }
+                    self.element(old_child, *child, &child_path)
+                }
+                None => self.push(&child_path, *child, ChangeKind::Added),
+            }
+        }
+        for (j, child) in old_children.iter().enumerate() {
+            if !old_matched[j] {
+                let path = child_path(&old_path, *child, j + 1);
+                self.push(&path, *child, ChangeKind::Removed);
+            }
+        }
+    }
+
+    /// The path of an element in the old tree
+    fn old_path(&self, node: Node) -> String {
+        let mut parts = vec![];
+        for ancestor in node.ancestors().filter(Node::is_element) {
+            let tag = ancestor.tag_name().name();
+            match ancestor.parent_element() {
+                Some(parent) => {
+                    let position = parent
+                        .children()
+                        .filter(Node::is_element)
+                        .position(|c| c == ancestor)
+                        .unwrap_or(0);
+                    parts.push(format!("{tag}:nth-child({})", position + 1));
+                }
+                None => parts.push(tag.to_string()),
+            }
+        }
+        parts.reverse();
+        parts.join(" > ")
+    }
+
+    fn push(&mut self, path: &str, node: Node, kind: ChangeKind) {
+        self.diff.changes.push(Change {
+            path: path.to_string(),
+            id: node.attribute("id").map(str::to_string),
+            kind,
+        });
+    }
+
+    fn equal(&self, a: Option<&str>, b: Option<&str>, path_data: bool) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => values_equal(a, b, self.tolerance, path_data),
+            (a, b) => a == b,
+        }
+    }
+}
+
+/// Marks the longest increasing run of the positions, the positions that are `None` are skipped
+fn longest_increasing(positions: &[Option<usize>]) -> Vec<bool> {
+    // the last index of the run of every length with the smallest last position
+    let mut tails: Vec<usize> = vec![];
+    let mut previous = vec![None; positions.len()];
+    for (i, position) in positions.iter().enumerate() {
+        if position.is_none() {
+            continue;
+        }
+        let len = tails.partition_point(|&t| positions[t] < *position);
+        if len > 0 {
+            previous[i] = Some(tails[len - 1]);
+        }
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+    let mut in_order = vec![false; positions.len()];
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        in_order[i] = true;
+        next = previous[i];
+    }
+    in_order
+}
+
+fn paths_by_id<'a, 'input>(root: Node<'a, 'input>) -> HashMap<&'a str, (Node<'a, 'input>, String)> {
+    let mut ids = HashMap::new();
+    let mut stack = vec![(root, root.tag_name().name().to_string())];
+    while let Some((node, path)) = stack.pop() {
+        if let Some(id) = node.attribute("id") {
+            ids.insert(id, (node, path.clone()));
+        }
+        for (i, child) in node.children().filter(Node::is_element).enumerate() {
+            let child_path = format!("{path} > {}:nth-child({})", child.tag_name().name(), i + 1);
+            stack.push((child, child_path));
+        }
+    }
+    ids
+}
+
+/// The character data of the element without the text of the child elements. The line breaks
+/// and tabs are added by the indentation and skipped, spaces are kept.
+fn text(node: Node) -> String {
+    node.children()
+        .filter(Node::is_text)
+        .filter_map(|c| c.text())
+        .flat_map(str::chars)
+        .filter(|c| !matches!(c, '\n' | '\r' | '\t'))
+        .collect()
+}
+
+/// The properties of the style attribute
+fn style<'a>(node: Node<'a, '_>) -> Vec<(&'a str, &'a str)> {
+    node.attribute("style")
+        .unwrap_or_default()
+        .split(';')
+        .filter_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            Some((property.trim(), value.trim()))
+        })
+        .collect()
+}
+
+fn property<'a>(style: &[(&'a str, &'a str)], name: &str) -> Option<&'a str> {
+    style.iter().find(|(p, _)| *p == name).map(|(_, v)| *v)
+}
+
+/// Compares the values with the numbers in them compared with a tolerance, `path_data` splits
+/// the commands of the `d` attribute from their first number.
+fn values_equal(a: &str, b: &str, tolerance: f64, path_data: bool) -> bool {
+    if a == b {
+        return true;
+    }
+    let (a, b) = (tokens(a, path_data), tokens(b, path_data));
+    a.len() == b.len()
+        && a.iter().zip(&b).all(|(a, b)| match (a, b) {
+            (Token::Number(a, a_unit), Token::Number(b, b_unit)) => {
+                a_unit == b_unit && (a - b).abs() <= tolerance
+            }
+            (a, b) => a == b,
+        })
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    /// a number and its unit, like `10px`
+    Number(f64, &'a str),
+    Other(&'a str),
+}
+
+/// Splits the value into the words between the separators and the separators. Only a whole
+/// word is a number, so colors like `#00e500` and ids like `bar-1` are compared as text.
+fn tokens(value: &str, path_data: bool) -> Vec<Token<'_>> {
+    let is_separator = |c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')');
+    let mut tokens = vec![];
+    let mut rest = value;
+    while let Some(first) = rest.chars().next() {
+        let len = match rest.find(is_separator) {
+            Some(0) => first.len_utf8(),
+            Some(len) => len,
+            None => rest.len(),
+        };
+        let (word, after) = rest.split_at(len);
+        rest = after;
+        if is_separator(first) {
+            tokens.push(Token::Other(word));
+        } else if let Some(number) = number(word) {
+            tokens.push(number);
+        } else if path_data
+            && first.is_ascii_alphabetic()
+            && let Some(number @ Token::Number(_, "")) = number(&word[1..])
+        {
+            tokens.push(Token::Other(&word[..1]));
+            tokens.push(number);
+        } else {
+            tokens.push(Token::Other(word));
+        }
+    }
+    tokens
+}
+
+/// Parses a word that is a number with an optional unit, like `1.5`, `10px` or `50%`
+fn number(word: &str) -> Option<Token<'_>> {
+    let len = number_prefix(word);
+    let unit = &word[len..];
+    if len == 0 || !unit.chars().all(|c| c.is_ascii_alphabetic() || c == '%') {
+        return None;
+    }
+    Some(Token::Number(word[..len].parse().ok()?, unit))
+}
+
+/// Length of the number at the start of the value, like `-1.5e3`
+fn number_prefix(value: &str) -> usize {
+    let bytes = value.as_bytes();
+    let mut i = 0;
+    if matches!(bytes.first(), Some(b'-' | b'+')) {
+        i += 1;
+    }
+    let digits_start = i;
+    while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+        i += 1;
+    }
+    if i == digits_start || !bytes[digits_start..i].iter().any(u8::is_ascii_digit) {
+        return 0;
+    }
+    if i < bytes.len() && matches!(bytes[i], b'e' | b'E') {
+        let mut j = i + 1;
+        if matches!(bytes.get(j), Some(b'-' | b'+')) {
+            j += 1;
+        }
+        if bytes.get(j).is_some_and(u8::is_ascii_digit) {
+            i = j;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::{Parent, color::Color, shapes::svg::Svg};
+
+    use super::*;
+
+    fn chart(heights: &[f64]) -> Element<Svg> {
+        Element::svg().push(
+            Element::group().id("bars").push_iter(
+                heights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, h)| Element::rect(i as u32 * 10, 0, 5, *h).id(&format!("bar-{i}"))),
+            ),
+        )
+    }
+
+    #[test]
+    fn unchanged() {
+        let diff = diff(&chart(&[1., 2.]), &chart(&[1., 2.0000001]));
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "no changes\n");
+
+        let diff = diff_with_tolerance(&chart(&[1.]), &chart(&[1.04]), 0.05);
+        assert!(diff.is_empty());
+        assert!(!super::diff(&chart(&[1.]), &chart(&[1.04])).is_empty());
+    }
+
+    #[test]
+    fn changes() {
+        let old = chart(&[1., 2., 3.])
+            .push(Element::text(0, 0).push("old"))
+            .push(Element::group().id("legend"));
+        let new = Element::svg()
+            .push(
+                Element::group()
+                    .id("bars")
+                    .push(Element::rect(20, 0, 5, 3).id("bar-2"))
+                    .push(Element::rect(0, 0, 5, 1).id("bar-0").fill(Color::Red)),
+            )
+            .push(Element::text(0, 0).push("new"))
+            .push(
+                Element::group()
+                    .id("legend")
+                    .push(Element::rect(10, 0, 5, 2).id("bar-1"))
+                    .push(Element::circle(0, 0, 1)),
+            );
+
+        let report = diff(&old, &new).to_string();
+        assert_eq!(
+            report,
+            concat!(
+                "> svg > g:nth-child(1) > rect:nth-child(1) #bar-2 ",
+                "(from svg > g:nth-child(1) > rect:nth-child(3))\n",
+                "~ svg > g:nth-child(1) > rect:nth-child(2) #bar-0: style fill (none) -> \"red\"\n",
+                "~ svg > text:nth-child(2): text \"old\" -> \"new\"\n",
+                "> svg > g:nth-child(3) > rect:nth-child(1) #bar-1 ",
+                "(from svg > g:nth-child(1) > rect:nth-child(2))\n",
+                "+ svg > g:nth-child(3) > circle:nth-child(2)\n",
+            )
+        );
+    }
+
+    #[test]
+    fn reordered() {
+        let svg = |ids: &[&str]| {
+            Element::svg().push_iter(ids.iter().map(|id| Element::rect(0, 0, 1, 1).id(id)))
+        };
+        assert_eq!(
+            diff(&svg(&["x", "y"]), &svg(&["y", "x"])).to_string(),
+            "> svg > rect:nth-child(1) #y (from svg > rect:nth-child(2))\n"
+        );
+        // the siblings after an inserted element are not moved
+        assert_eq!(
+            diff(&svg(&["x", "y"]), &svg(&["new", "x", "y"])).to_string(),
+            "+ svg > rect:nth-child(1) #new\n"
+        );
+    }
+
+    #[test]
+    fn text_whitespace() {
+        let text = |s: &str| Element::svg().push(Element::text(0, 0).push(s.to_string()));
+        assert_eq!(
+            diff(&text("label"), &text(" label")).to_string(),
+            "~ svg > text:nth-child(1): text \"label\" -> \" label\"\n"
+        );
+        // the indentation of a rendered tree is not text
+        let formatted = text("label").render(None);
+        assert!(formatted.contains("\nlabel\t</text>"));
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        let unformatted = text("label").render(Some(opts));
+        assert!(diff_rendered(&formatted, &unformatted, DEFAULT_TOLERANCE).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let old = Element::svg()
+            .push(Element::rect(0, 0, 1, 1))
+            .push(Element::circle(0, 0, 1).id("dot"));
+        let new = Element::svg()
+            .push(Element::circle(0, 0, 1))
+            .push(Element::rect(0, 0, 1, 2));
+        let changes = diff(&old, &new).changes;
+        let kinds = changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind.clone()))
+            .collect::<Vec<_>>();
+        let height = ChangeKind::Attribute {
+            name: "height".to_string(),
+            old: Some("1".to_string()),
+            new: Some("2".to_string()),
+        };
+        assert_eq!(
+            kinds,
+            [
+                ("svg > circle:nth-child(1)", ChangeKind::Added),
+                ("svg > rect:nth-child(2)", height),
+                ("svg > circle:nth-child(2)", ChangeKind::Removed),
+            ]
+        );
+        assert_eq!(changes[2].id.as_deref(), Some("dot"));
+    }
+
+    #[rstest]
+    #[case("1", "1.0000001", true)]
+    #[case("1", "1.1", false)]
+    #[case("translate(10, 5.0000001)", "translate(10, 5)", true)]
+    #[case("translate(10, 5)", "scale(10, 5)", false)]
+    #[case("1e3", "1000", true)]
+    #[case("url(#a-1)", "url(#a-1)", true)]
+    #[case("10px", "10", false)]
+    #[case("1 2", "1 2 3", false)]
+    #[case("1 2", "1,2", false)]
+    #[case("50%", "50.0000001%", true)]
+    fn numbers(#[case] a: &str, #[case] b: &str, #[case] equal: bool) {
+        assert_eq!(values_equal(a, b, DEFAULT_TOLERANCE, false), equal);
+    }
+
+    #[rstest]
+    #[case("#00e500", "#000000")]
+    #[case("#123456", "#123457")]
+    #[case("bar-1", "bar-2")]
+    #[case("url(#a1)", "url(#a2)")]
+    #[case("M10,20 L30,40", "M10,20 l30,40")]
+    fn not_numbers(#[case] a: &str, #[case] b: &str) {
+        assert!(!values_equal(a, b, 10., false), "{a} {b}");
+        assert!(!values_equal(a, b, 10., true), "{a} {b}");
+    }
+
+    #[test]
+    fn path_data() {
+        let (a, b) = ("M10,20 L30,40 Z", "M10.0000001,20 L30,40.0000001 Z");
+        assert!(values_equal(a, b, DEFAULT_TOLERANCE, true));
+        assert!(!values_equal(a, b, DEFAULT_TOLERANCE, false));
+        let path = |d| Element::path().attr("d", d);
+        assert!(diff(&path(a), &path(b)).is_empty());
+    }
+}
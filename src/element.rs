@@ -1,6 +1,7 @@
 use std::{
     any::Any,
-    fmt::Debug,
+    fmt::{self, Debug},
+    io,
     ops::{Deref, DerefMut},
 };
 
@@ -8,27 +9,43 @@ use crate::{
     Options,
     buffer::Buffer,
     color::Color,
+    diagnostics::{Checks, Diagnostic, Rendered},
     marker_traits::*,
-    style::{FillRule, LineCap, LineJoin, Style},
+    node::Node,
+    style::{
+        AlignmentBaseline, BaselineShift, DominantBaseline, FillRule, FontStretch, FontStyle,
+        FontVariant, FontWeight, LineCap, LineJoin, Style, TextDecoration, WritingMode,
+    },
     units::{Length, Px},
     visit::Visit,
 };
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: serde::Serialize + 'static",
+        deserialize = "T: serde::Deserialize<'de> + crate::Adopt"
+    ))
+)]
 pub struct Element<T> {
     pub id: Option<String>,
-    class: Option<String>,
+    pub(crate) class: Option<String>,
     /// NOTE: this style object contains all possible styles, and some might not be applicable to
     /// the current element,
     pub(crate) style: Style,
-    transforms: Option<Vec<Transform>>,
+    pub(crate) transforms: Option<Vec<Transform>>,
     hx: Option<HxData>,
     pub(crate) kind: T,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::children"))]
     children: Vec<Box<dyn ChildOf<T>>>,
     /// higher number is closer to the screen.
     /// this property is not rendered, only used to sort the rendering order internally
     pub(crate) z_index: Option<i32>,
     path_length: Option<Px>,
+    /// attributes that are not modeled by the element kind, rendered as is after the style
+    pub(crate) attrs: Vec<(String, String)>,
 }
 
 // Parent of Line for Element<svg>
@@ -72,6 +89,24 @@ impl<T: ElementKind + Visit> From<T> for Element<T> {
     }
 }
 
+// not derived, the boxed children needs `T: 'static` to be cloned
+impl<T: Clone + 'static> Clone for Element<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            class: self.class.clone(),
+            style: self.style.clone(),
+            transforms: self.transforms.clone(),
+            hx: self.hx.clone(),
+            kind: self.kind.clone(),
+            children: self.children.clone(),
+            z_index: self.z_index,
+            path_length: self.path_length,
+            attrs: self.attrs.clone(),
+        }
+    }
+}
+
 impl<T: Visit + 'static + ElementKind> BaseElement for Element<T> {
     fn as_any(&self) -> &dyn Any {
         self
@@ -86,6 +121,90 @@ impl<T: Visit + 'static + ElementKind> BaseElement for Element<T> {
     // }
 }
 
+impl<T: Visit + 'static + ElementKind> Node for Element<T> {
+    fn tag(&self) -> &str {
+        self.kind.tag()
+    }
+
+    fn get_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn id_mut(&mut self) -> &mut Option<String> {
+        &mut self.id
+    }
+
+    fn get_class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    fn class_mut(&mut self) -> &mut Option<String> {
+        &mut self.class
+    }
+
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    fn style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    fn get_transforms(&self) -> &[Transform] {
+        self.transforms.as_deref().unwrap_or_default()
+    }
+
+    fn transforms_mut(&mut self) -> &mut Option<Vec<Transform>> {
+        &mut self.transforms
+    }
+
+    fn get_attrs(&self) -> &[(String, String)] {
+        self.kind.attrs().unwrap_or(&self.attrs)
+    }
+
+    fn attrs_mut(&mut self) -> &mut Vec<(String, String)> {
+        match self.kind.attrs_mut() {
+            Some(attrs) => attrs,
+            None => &mut self.attrs,
+        }
+    }
+
+    fn href_mut(&mut self) -> Option<&mut String> {
+        self.kind.href_mut()
+    }
+
+    fn attributes(&self) -> Vec<(String, String)> {
+        let mut buffer = Buffer::with_capacity(100);
+        buffer.push_tag(self.kind.tag());
+        self.visit_attrs(&mut buffer);
+        buffer.attributes()
+    }
+
+    fn character_data(&self) -> Vec<&str> {
+        self.children
+            .iter()
+            .filter_map(|c| {
+                let any = c.as_any();
+                any.downcast_ref::<String>()
+                    .map(String::as_str)
+                    .or_else(|| any.downcast_ref::<&'static str>().copied())
+            })
+            .collect()
+    }
+
+    fn child_nodes(&self) -> Vec<&dyn Node> {
+        let mut nodes = self.kind.nodes();
+        nodes.extend(self.children.iter().filter_map(|c| c.as_node()));
+        nodes
+    }
+
+    fn child_nodes_mut(&mut self) -> Vec<&mut dyn Node> {
+        let mut nodes = self.kind.nodes_mut();
+        nodes.extend(self.children.iter_mut().filter_map(|c| c.as_node_mut()));
+        nodes
+    }
+}
+
 impl<T> Deref for Element<T> {
     type Target = T;
 
@@ -100,6 +219,31 @@ impl<T> DerefMut for Element<T> {
     }
 }
 
+/// Writer that drops the output
+struct Discard;
+
+impl fmt::Write for Discard {
+    fn write_str(&mut self, _s: &str) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Adapts an `io::Write` for [`Buffer`], that writes to a `fmt::Write`. The io error is kept
+/// since `fmt::Error` has no details.
+struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
 impl<T: Visit + ElementKind> Visit for Element<T> {
     fn visit(&self, buffer: &mut Buffer) {
         // special case for naked strings, like inside <text> THIS STRING </text>.
@@ -109,25 +253,38 @@ impl<T: Visit + ElementKind> Visit for Element<T> {
         } else if T::TAG == "EMPTY" {
             return;
         }
-        buffer.push_tag(T::TAG);
-        buffer.push_attr_opt("id", &self.id);
-        buffer.push_attr_opt("class", &self.class);
-        self.hx.visit(buffer);
-        self.kind.visit(buffer);
-        buffer.push_attr_opt("pathLength", &self.path_length);
-        buffer.push_attr_opt("transform", &self.transforms);
-        self.style.visit(buffer);
+        buffer.enter(self.kind.tag(), self.id.as_deref());
+        buffer.push_tag(self.kind.tag());
+        self.visit_attrs(buffer);
 
         // TODO: Sort children on z index and render them in order of small to big.
 
-        if self.children.is_empty() {
+        if self.children.is_empty() && !self.kind.has_content() {
             buffer.push_tag_self_close();
         } else {
             buffer.push_tag_end();
+            self.kind.visit_content(buffer);
             for child in &self.children {
                 child.visit(buffer);
             }
-            buffer.push_tag_close(T::TAG);
+            buffer.push_tag_close(self.kind.tag());
+        }
+        buffer.leave();
+    }
+}
+
+impl<T: Visit + ElementKind> Element<T> {
+    /// Pushes the attributes of the start tag
+    fn visit_attrs(&self, buffer: &mut Buffer) {
+        buffer.push_attr_opt("id", &self.id);
+        buffer.push_attr_opt("class", &self.class);
+        self.hx.visit(buffer);
+        self.kind.visit(buffer);
+        buffer.push_attr_opt("pathLength", &self.path_length);
+        buffer.push_attr_opt("transform", &self.transforms);
+        self.style.visit(buffer);
+        for (name, value) in &self.attrs {
+            buffer.push_extra_attr(name, value);
         }
     }
 }
@@ -153,6 +310,76 @@ where
     }
 }
 
+impl<T: 'static> Element<T> {
+    /// Iterates over the direct children, use [`ChildOf::as_any`] or `downcast_ref` on
+    /// `dyn ChildOf` to get the concrete type of a child.
+    pub fn children(&self) -> impl Iterator<Item = &dyn ChildOf<T>> {
+        self.children.iter().map(|c| c.as_ref())
+    }
+
+    pub fn children_mut(&mut self) -> impl Iterator<Item = &mut dyn ChildOf<T>> {
+        self.children.iter_mut().map(|c| c.as_mut())
+    }
+
+    /// Removes every child and returns them, they can be added back with
+    /// [`Element::push_boxed`].
+    pub fn take_children(&mut self) -> Vec<Box<dyn ChildOf<T>>> {
+        std::mem::take(&mut self.children)
+    }
+
+    /// Inserts the child at the index, the children after it are shifted to the right.
+    /// Panics if `index > len`.
+    pub fn insert_at<C: ChildOf<T>>(&mut self, index: usize, child: C) {
+        self.children.insert(index, Box::new(child));
+    }
+
+    /// Removes the direct child with the id and returns it.
+    /// ```
+    /// # use svg_maker::{Parent, element::Element, shapes::rect::Rect};
+    /// let mut svg = Element::svg()
+    ///     .push(Element::rect(0, 0, 1, 1).id("a"))
+    ///     .push(Element::rect(0, 0, 2, 2).id("b"));
+    /// let a = svg.remove_by_id("a").unwrap();
+    /// assert!(a.downcast_ref::<Element<Rect>>().is_some());
+    /// assert_eq!(svg.len(), 1);
+    /// ```
+    pub fn remove_by_id(&mut self, id: &str) -> Option<Box<dyn ChildOf<T>>> {
+        let index = self.position_by_id(id)?;
+        Some(self.children.remove(index))
+    }
+
+    /// Replaces the direct child with the id, the replaced child is returned.
+    /// The new child is not added if no child has the id.
+    pub fn replace_by_id<C: ChildOf<T>>(
+        &mut self,
+        id: &str,
+        child: C,
+    ) -> Option<Box<dyn ChildOf<T>>> {
+        let index = self.position_by_id(id)?;
+        Some(std::mem::replace(
+            &mut self.children[index],
+            Box::new(child),
+        ))
+    }
+
+    /// Keeps the children that the predicate returns true for.
+    /// ```
+    /// # use svg_maker::{Parent, element::Element, shapes::circle::Circle};
+    /// let mut group = Element::group()
+    ///     .push(Element::rect(0, 0, 1, 1))
+    ///     .push(Element::circle(0, 0, 1));
+    /// group.retain(|child| child.downcast_ref::<Element<Circle>>().is_none());
+    /// assert_eq!(group.len(), 1);
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(&dyn ChildOf<T>) -> bool) {
+        self.children.retain(|c| f(c.as_ref()));
+    }
+
+    fn position_by_id(&self, id: &str) -> Option<usize> {
+        self.children.iter().position(|c| c.get_id() == Some(id))
+    }
+}
+
 impl<T: ElementKind + Visit> Element<T> {
     pub fn new(kind: T) -> Self {
         Self {
@@ -165,6 +392,7 @@ impl<T: ElementKind + Visit> Element<T> {
             children: vec![],
             z_index: Some(0),
             path_length: None,
+            attrs: vec![],
         }
     }
 
@@ -183,15 +411,159 @@ impl<T: ElementKind + Visit> Element<T> {
         self
     }
 
+    /// Sets an attribute that isn't modeled by the element, like `data-*` attributes.
+    /// Setting the same attribute again replaces the value. The value is escaped when rendered,
+    /// an attribute that the element already renders, or with an invalid name, is skipped with
+    /// a warning.
+    /// ```
+    /// # use svg_maker::element::Element;
+    /// let rect = Element::rect(0, 0, 10, 10).attr("data-value", 42);
+    /// assert!(rect.render(None).contains(r#"data-value="42""#));
+    /// ```
+    pub fn attr(mut self, name: &str, value: impl ToString) -> Self {
+        let attrs = match self.kind.attrs_mut() {
+            Some(attrs) => attrs,
+            None => &mut self.attrs,
+        };
+        let value = value.to_string();
+        match attrs.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => attrs.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    /// Returns the value of an attribute set with [`Element::attr`] or kept by the parser.
+    pub fn get_attr(&self, name: &str) -> Option<&str> {
+        self.kind
+            .attrs()
+            .unwrap_or(&self.attrs)
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
     pub fn render(&self, opts: Option<Options>) -> String {
         let mut buffer = Buffer::with_capacity(100);
         if let Some(opt) = opts {
             buffer.opts = opt;
         }
-        self.visit(&mut buffer);
+        self.visit_root(&mut buffer);
         buffer.str().to_string()
     }
 
+    /// Renders the element and checks it for problems like duplicate ids, references to ids
+    /// that don't exist, negative sizes and invalid numbers, see [`Code`] for every check.
+    /// ```
+    /// # use svg_maker::{Parent, element::Element, diagnostics::Code};
+    /// let svg = Element::svg()
+    ///     .push(Element::rect(0, 0, -10., 10).id("bar"))
+    ///     .push(Element::use_href(0, 0).href("missing"))
+    ///     .render_checked(None);
+    /// let codes = svg.diagnostics.iter().map(|d| d.code).collect::<Vec<_>>();
+    /// assert_eq!(codes, [Code::NegativeSize, Code::UnresolvedReference]);
+    /// ```
+    pub fn render_checked(&self, opts: Option<Options>) -> Rendered {
+        let mut buffer = Buffer::with_capacity(100);
+        if let Some(opt) = opts {
+            buffer.opts = opt;
+        }
+        buffer.checks = Some(Checks::default());
+        self.visit_root(&mut buffer);
+        let diagnostics = buffer.checks.take().map(Checks::finish).unwrap_or_default();
+        Rendered {
+            output: buffer.str().to_string(),
+            diagnostics,
+        }
+    }
+
+    /// Checks that the ids are unique, that every `url(#id)` and href references an element
+    /// that exists and that every definition of the svg is used.
+    /// ```
+    /// # use svg_maker::{Parent, element::Element};
+    /// let svg = Element::svg()
+    ///     .def(Element::circle(0, 0, 1).id("dot"))
+    ///     .def(Element::rect(0, 0, 1, 1).id("unused"))
+    ///     .push(Element::use_href(0, 0).href("dot"));
+    /// let diagnostics = svg.check_references();
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].id.as_deref(), Some("unused"));
+    /// ```
+    pub fn check_references(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.check_pass().finish();
+        diagnostics.retain(|d| d.code.is_reference());
+        diagnostics
+    }
+
+    /// Visits the element as the root of a render
+    fn visit_root(&self, buffer: &mut Buffer) {
+        if buffer.opts.optimizations.remove_unused_defs {
+            buffer.used_ids = Some(self.check_pass().used_ids());
+        }
+        self.visit(buffer);
+    }
+
+    /// Visits the tree without keeping the output, to collect the ids and references
+    fn check_pass(&self) -> Checks {
+        let mut discard = Discard;
+        let mut buffer = Buffer::with_sink(&mut discard, Options::default());
+        buffer.checks = Some(Checks::default());
+        self.visit(&mut buffer);
+        buffer.checks.take().unwrap_or_default()
+    }
+
+    /// Renders the element into the writer, the output is written in chunks while the tree is
+    /// visited so the whole document is never held in memory.
+    /// ```
+    /// # use svg_maker::element::Element;
+    /// let mut svg = String::new();
+    /// Element::rect(0, 0, 10, 10).render_fmt(&mut svg, None).unwrap();
+    /// assert!(svg.starts_with("<rect"));
+    /// ```
+    pub fn render_fmt<W: fmt::Write>(&self, writer: &mut W, opts: Option<Options>) -> fmt::Result {
+        let mut buffer = Buffer::with_sink(writer, opts.unwrap_or_default());
+        self.visit_root(&mut buffer);
+        buffer.flush()
+    }
+
+    /// Same as [`Element::render_fmt`] for a byte writer like a file or a socket. Wrap unbuffered
+    /// writers in a [`std::io::BufWriter`].
+    pub fn render_to<W: io::Write>(&self, writer: W, opts: Option<Options>) -> io::Result<()> {
+        let mut writer = IoWriter {
+            inner: writer,
+            error: None,
+        };
+        match self.render_fmt(&mut writer, opts) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(writer
+                .error
+                .unwrap_or_else(|| io::Error::other("failed to render the element"))),
+        }
+    }
+
+    /// Moves the attributes (id, class, style, transforms..) over to an element of another kind,
+    /// the children are dropped.
+    pub(crate) fn with_kind<U>(self, kind: U) -> Element<U> {
+        Element {
+            id: self.id,
+            class: self.class,
+            style: self.style,
+            transforms: self.transforms,
+            hx: self.hx,
+            kind,
+            children: vec![],
+            z_index: self.z_index,
+            path_length: self.path_length,
+            attrs: self.attrs,
+        }
+    }
+
+    /// Adds a child that is already boxed, like the children from [`Element::take_children`].
+    pub fn push_boxed(mut self, child: Box<dyn ChildOf<T>>) -> Self {
+        self.children.push(child);
+        self
+    }
+
     /// Returns the number of children the element have
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
@@ -270,7 +642,7 @@ impl<T: BaseStyle> Element<T> {
     }
 
     pub fn style_kv(mut self, key: &str, value: impl ToString) -> Self {
-        self.style.kv.insert(key.to_string(), value.to_string());
+        self.style.insert_kv(key, value.to_string());
         self
     }
 }
@@ -282,6 +654,75 @@ impl<T: OpenEndedShape> Element<T> {
     }
 }
 
+impl<T: TextElement> Element<T> {
+    /// Sets the font family, a comma separated list of families is passed through as is.
+    pub fn font_family(mut self, family: &str) -> Self {
+        self.style.font_family = Some(family.to_string());
+        self
+    }
+
+    pub fn font_size(mut self, size: impl Into<Length>) -> Self {
+        self.style.font_size = Some(size.into());
+        self
+    }
+
+    pub fn font_weight(mut self, weight: FontWeight) -> Self {
+        self.style.font_weight = Some(weight);
+        self
+    }
+
+    pub fn font_style(mut self, style: FontStyle) -> Self {
+        self.style.font_style = Some(style);
+        self
+    }
+
+    pub fn font_variant(mut self, variant: FontVariant) -> Self {
+        self.style.font_variant = Some(variant);
+        self
+    }
+
+    pub fn font_stretch(mut self, stretch: FontStretch) -> Self {
+        self.style.font_stretch = Some(stretch);
+        self
+    }
+
+    pub fn dominant_baseline(mut self, baseline: DominantBaseline) -> Self {
+        self.style.dominant_baseline = Some(baseline);
+        self
+    }
+
+    pub fn alignment_baseline(mut self, baseline: AlignmentBaseline) -> Self {
+        self.style.alignment_baseline = Some(baseline);
+        self
+    }
+
+    /// `Length`s, `Px`, numbers etc. implements `Into<BaselineShift>`
+    pub fn baseline_shift(mut self, shift: impl Into<BaselineShift>) -> Self {
+        self.style.baseline_shift = Some(shift.into());
+        self
+    }
+
+    pub fn writing_mode(mut self, mode: WritingMode) -> Self {
+        self.style.writing_mode = Some(mode);
+        self
+    }
+
+    pub fn letter_spacing(mut self, spacing: impl Into<Length>) -> Self {
+        self.style.letter_spacing = Some(spacing.into());
+        self
+    }
+
+    pub fn word_spacing(mut self, spacing: impl Into<Length>) -> Self {
+        self.style.word_spacing = Some(spacing.into());
+        self
+    }
+
+    pub fn text_decoration(mut self, decoration: TextDecoration) -> Self {
+        self.style.text_decoration = Some(decoration);
+        self
+    }
+}
+
 impl<T: Shape> Element<T> {
     pub fn path_length(mut self, len: impl Into<Px>) -> Self {
         self.path_length = Some(len.into());
@@ -318,7 +759,8 @@ impl<T: Hx> Element<T> {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct HxData {
     ext: Option<String>,
     connect: Option<String>,
@@ -364,7 +806,8 @@ impl Visit for HxData {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Transform {
     // x,y y is assumed 0 if leftout
     Translate(f64, f64),
@@ -384,23 +827,172 @@ pub enum Transform {
     // angle
     SkewY(f64),
 
-    // TODO: ..
-    Matrix(i32, i32, i32, i32, i32, i32),
+    // a b c d e f
+    Matrix(f64, f64, f64, f64, f64, f64),
 }
 
 impl Visit for Transform {
     fn visit(&self, buffer: &mut Buffer) {
-        let str = match self {
-            Transform::Translate(x, y) => format!("translate({} {})", x, y),
-            Transform::TranslateX(x) => format!("translate({})", x),
-            Transform::TranslateY(y) => format!("translate(0 {})", y),
-            Transform::Scale(x, y) => format!("scale({} {})", x, y),
-            Transform::ScaleX(x) => format!("scale({} 1)", x),
-            Transform::ScaleY(y) => format!("scale(1 {})", y),
-            Transform::ScaleXY(xy) => format!("scale({})", xy),
-            _ => todo!("not yet implemented"),
+        let (name, args): (&str, &[f64]) = match self {
+            Transform::Translate(x, y) => ("translate", &[*x, *y]),
+            Transform::TranslateX(x) => ("translate", &[*x]),
+            Transform::TranslateY(y) => ("translate", &[0., *y]),
+            Transform::Scale(x, y) => ("scale", &[*x, *y]),
+            Transform::ScaleX(x) => ("scale", &[*x, 1.]),
+            Transform::ScaleY(y) => ("scale", &[1., *y]),
+            Transform::ScaleXY(xy) => ("scale", &[*xy]),
+            Transform::RotateXY(a, x, y) => ("rotate", &[*a, *x, *y]),
+            Transform::Rotate(a) => ("rotate", &[*a]),
+            Transform::SkewX(a) => ("skewX", &[*a]),
+            Transform::SkewY(a) => ("skewY", &[*a]),
+            Transform::Matrix(a, b, c, d, e, f) => ("matrix", &[*a, *b, *c, *d, *e, *f]),
         };
 
-        buffer.push_str(&str);
+        buffer.push_str(name);
+        buffer.push('(');
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                buffer.push_space();
+            }
+            buffer.push_number(*arg);
+        }
+        buffer.push(')');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parent;
+
+    use super::*;
+
+    #[test]
+    fn extra_attributes() {
+        let rect = Element::rect(0, 0, 10, 10)
+            .attr("data-value", 1)
+            .attr("data-value", "a & b")
+            .attr("width", 20);
+        assert_eq!(rect.get_attr("data-value"), Some("a & b"));
+
+        let rendered = rect.render_checked(None);
+        assert_eq!(
+            rendered.output,
+            "<rect x=\"0\" y=\"0\" width=\"10\" height=\"10\" data-value=\"a &amp; b\"/>\n"
+        );
+        assert_eq!(rendered.diagnostics.len(), 1);
+        assert_eq!(
+            rendered.diagnostics[0].message,
+            "skipped duplicated attribute: width"
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        // large enough to be flushed several times
+        let svg = Element::svg().push_iter((0..2000).map(|i| {
+            Element::rect(i, 0, 10, 10)
+                .style_kv("--i", i)
+                .attr("data-i", i)
+        }));
+        let sorted = || Options {
+            sort_attributes: true,
+            ..Default::default()
+        };
+        let expected = svg.render(Some(sorted()));
+
+        let mut streamed = String::new();
+        svg.render_fmt(&mut streamed, Some(sorted())).unwrap();
+        assert_eq!(streamed, expected);
+
+        let mut bytes = vec![];
+        svg.render_to(&mut bytes, Some(sorted())).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+
+        let full = &mut [0_u8; 100][..];
+        let err = svg.render_to(full, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn style_order() {
+        let rect = Element::rect(0, 0, 10, 10)
+            .attr("data-z", 1)
+            .style_kv("--z", 1)
+            .style_kv("--a", 2)
+            .fill(Color::Red)
+            .style_kv("--z", 3);
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        assert_eq!(
+            rect.render(Some(opts)),
+            r#"<rect x="0" y="0" width="10" height="10" style="fill: red; --z:3; --a:2;" data-z="1"/>"#
+        );
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.sort_attributes = true;
+        assert_eq!(
+            rect.render(Some(opts)),
+            r#"<rect data-z="1" height="10" style="--a:2; --z:3; fill: red;" width="10" x="0" y="0"/>"#
+        );
+    }
+
+    #[test]
+    fn clone() {
+        let bar = Element::group()
+            .class("bar")
+            .push(Element::rect(0, 0, 10, 100).fill(Color::Red))
+            .push(Element::text(5, 110).push("label".to_string()));
+        let svg = Element::svg().def(Element::circle(0, 0, 1).id("dot"));
+
+        let bars = (0..3).map(|i| {
+            bar.clone()
+                .transform(Transform::TranslateX(i as f64 * 20.))
+                .id(&format!("bar-{i}"))
+        });
+        let first = bar
+            .clone()
+            .fill(Color::Black)
+            .push(Element::use_href(0, 0).href("dot"));
+        let copy = svg.clone().push_iter(bars).push(first);
+        assert_eq!(copy.len(), 4);
+        assert_eq!(bar.len(), 2);
+        assert_eq!(svg.len(), 0);
+        assert_eq!(bar.render(None), bar.clone().render(None));
+
+        let rendered = copy.render(None);
+        assert_eq!(rendered.matches("<rect").count(), 4);
+        assert!(rendered.contains(r#"<circle id="dot" cx="0" cy="0" r="1"/>"#));
+    }
+
+    #[test]
+    fn mutation() {
+        use crate::shapes::{circle::Circle, rect::Rect};
+
+        let mut svg = Element::svg()
+            .push(Element::rect(0, 0, 1, 1).id("a"))
+            .push(Element::circle(0, 0, 1).id("b"))
+            .push(Element::rect(0, 0, 2, 2));
+
+        let old = svg.replace_by_id("b", Element::group().id("c")).unwrap();
+        assert!(old.is::<Element<Circle>>());
+        assert!(svg.replace_by_id("b", Element::group()).is_none());
+        assert!(svg.remove_by_id("missing").is_none());
+
+        svg.insert_at(0, Element::circle(1, 1, 1).id("first"));
+        for child in svg.children_mut() {
+            if let Some(rect) = child.downcast_mut::<Element<Rect>>() {
+                rect.class = Some("bar".to_string());
+            }
+        }
+        let ids = svg.children().map(|c| c.get_id()).collect::<Vec<_>>();
+        assert_eq!(ids, [Some("first"), Some("a"), Some("c"), None]);
+
+        svg.retain(|c| !c.is::<Element<Circle>>());
+        let children = svg.take_children();
+        assert_eq!((children.len(), svg.len()), (3, 0));
+        let svg = children.into_iter().fold(svg, |svg, c| svg.push_boxed(c));
+        assert_eq!(svg.render(None).matches(r#"class="bar""#).count(), 2);
     }
 }
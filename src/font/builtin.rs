@@ -0,0 +1,167 @@
+// ===== Built-in metrics =======================================================
+//
+// Advance widths for the printable ASCII range (0x20..=0x7E) from the Adobe AFM files of the
+// standard PDF fonts, in units of 1/1000 em. Characters outside the range use the width of the
+// space as fallback.
+//
+// Shipped: Helvetica, Helvetica-Bold, Times-Roman and Courier.
+// Oblique and italic variants share the widths of the upright font. Arial, Times New Roman and
+// Courier New are metric compatible with the fonts they replace and use their widths as is.
+//
+// Other families fall back to the closest table and are reported as approximate, see
+// `Font::is_approximate`: Inter, system-ui and sans-serif use Helvetica, serif, Times-Bold and
+// Times-Italic use Times-Roman and monospace uses Courier. Load the font file for exact metrics.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::font::metrics::FontMetrics;
+
+#[rustfmt::skip]
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[rustfmt::skip]
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+#[rustfmt::skip]
+const TIMES_ROMAN: [u16; 95] = [
+    250, 333, 408, 500, 500, 833, 778, 180, 333, 333, 500, 564, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444,
+    921, 722, 667, 667, 722, 611, 556, 722, 722, 333, 389, 722, 611, 889, 722, 722,
+    556, 722, 667, 556, 611, 722, 722, 944, 722, 722, 611, 333, 278, 333, 469, 500,
+    333, 444, 500, 444, 500, 444, 333, 500, 500, 278, 278, 500, 278, 778, 500, 500,
+    500, 500, 333, 389, 278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541,
+];
+
+const COURIER: [u16; 95] = [600; 95];
+
+#[derive(Clone, Copy)]
+enum Builtin {
+    Helvetica,
+    HelveticaBold,
+    TimesRoman,
+    Courier,
+}
+
+impl Builtin {
+    /// Returns the table of the family and true if the widths are the widths of the family,
+    /// false if the table is a fallback.
+    fn resolve(family: &str) -> Option<(Self, bool)> {
+        let family = family.trim().trim_matches(['"', '\'']).to_lowercase();
+        let builtin = match family.as_str() {
+            "helvetica" | "helvetica-oblique" | "arial" | "arial-italic" => {
+                (Builtin::Helvetica, true)
+            }
+            "inter" | "intervariable" | "sans-serif" | "system-ui" => (Builtin::Helvetica, false),
+            "helvetica-bold" | "helvetica-boldoblique" | "arial-bold" => {
+                (Builtin::HelveticaBold, true)
+            }
+            "times" | "times-roman" | "times new roman" => (Builtin::TimesRoman, true),
+            "times-bold" | "times-italic" | "times-bolditalic" | "serif" => {
+                (Builtin::TimesRoman, false)
+            }
+            "courier"
+            | "courier-bold"
+            | "courier-oblique"
+            | "courier-boldoblique"
+            | "courier new" => (Builtin::Courier, true),
+            "monospace" => (Builtin::Courier, false),
+            _ => return None,
+        };
+        Some(builtin)
+    }
+
+    fn metrics(self) -> &'static FontMetrics {
+        static HELVETICA_METRICS: OnceLock<FontMetrics> = OnceLock::new();
+        static HELVETICA_BOLD_METRICS: OnceLock<FontMetrics> = OnceLock::new();
+        static TIMES_ROMAN_METRICS: OnceLock<FontMetrics> = OnceLock::new();
+        static COURIER_METRICS: OnceLock<FontMetrics> = OnceLock::new();
+
+        match self {
+            Builtin::Helvetica => HELVETICA_METRICS.get_or_init(|| from_afm(&HELVETICA, 718, -207)),
+            Builtin::HelveticaBold => {
+                HELVETICA_BOLD_METRICS.get_or_init(|| from_afm(&HELVETICA_BOLD, 718, -207))
+            }
+            Builtin::TimesRoman => {
+                TIMES_ROMAN_METRICS.get_or_init(|| from_afm(&TIMES_ROMAN, 683, -217))
+            }
+            Builtin::Courier => COURIER_METRICS.get_or_init(|| from_afm(&COURIER, 629, -157)),
+        }
+    }
+}
+
+fn from_afm(widths: &[u16; 95], ascent: i16, descent: i16) -> FontMetrics {
+    let advances = (' '..='~')
+        .zip(widths.iter().copied())
+        .collect::<HashMap<_, _>>();
+    FontMetrics {
+        units_per_em: 1000,
+        ascent,
+        descent,
+        // AFM files has no line gap, use the common line height of 1.2em
+        line_gap: 1200 - (ascent - descent),
+        default_advance: widths[0],
+        advances,
+        kerning: HashMap::new(),
+    }
+}
+
+impl FontMetrics {
+    /// Returns the built-in metrics for the first family in a css font-family list that has
+    /// built-in metrics, ie "Inter, sans-serif".
+    /// Families without a table of their own get the widths of a similar font, see
+    /// [`Font::is_approximate`](crate::font::Font::is_approximate).
+    pub fn builtin(family: &str) -> Option<&'static FontMetrics> {
+        builtin_match(family).map(|(metrics, _)| metrics)
+    }
+}
+
+/// The built-in metrics of a font-family list and true if they are the metrics of the family
+pub(crate) fn builtin_match(family: &str) -> Option<(&'static FontMetrics, bool)> {
+    family
+        .split(',')
+        .find_map(Builtin::resolve)
+        .map(|(builtin, exact)| (builtin.metrics(), exact))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::font::Font;
+
+    #[test]
+    fn fallbacks_are_approximate() {
+        assert!(!Font::new("Helvetica", 10.).is_approximate());
+        assert!(!Font::new("'Arial', sans-serif", 10.).is_approximate());
+        assert!(!Font::new("Courier New", 10.).is_approximate());
+
+        // measured with the Helvetica widths, but reported as approximate
+        let inter = Font::new("Inter, sans-serif", 10.);
+        assert!(inter.is_approximate());
+        assert_eq!(
+            inter.text_width("Hi"),
+            Font::new("Helvetica", 10.).text_width("Hi")
+        );
+        assert!(Font::new("serif", 10.).is_approximate());
+        assert!(Font::new("Unknown", 10.).is_approximate());
+
+        let metrics = Font::new("Helvetica", 10.).metrics().unwrap().clone();
+        assert!(
+            !Font::new("Inter", 10.)
+                .with_metrics(metrics)
+                .is_approximate()
+        );
+    }
+}
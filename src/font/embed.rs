@@ -0,0 +1,183 @@
+// ===== Font embedding =========================================================
+
+use std::{collections::BTreeSet, fmt::Write, path::Path as FsPath};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::{
+    element::Element,
+    font::{FontError, subset::subset},
+    node::{Node, Walk},
+    shapes::svg::Svg,
+};
+
+impl Element<Svg> {
+    /// Embeds a subset of the font as an `@font-face` rule in the stylesheet of the svg, so that
+    /// the document renders the same on machines without the font.
+    /// Only the characters of the text that uses the family are kept, text without a font
+    /// family is assumed to get the family from the stylesheet and is included as well.
+    /// Must be called after the text has been added to the svg. Only fonts with TrueType
+    /// outlines can be subset, CFF based fonts are an [`FontError::Unsupported`] error.
+    /// ```no_run
+    /// # use svg_maker::{Parent, element::Element};
+    /// let svg = Element::svg()
+    ///     .css("text { font-family: Inter; }")
+    ///     .push(Element::text(0, 20).push("Revenue"))
+    ///     .embed_font("Inter", "fonts/Inter-Regular.ttf")
+    ///     .unwrap();
+    /// ```
+    pub fn embed_font(
+        mut self,
+        family: &str,
+        font_file: impl AsRef<FsPath>,
+    ) -> Result<Self, FontError> {
+        let data = std::fs::read(font_file).map_err(|e| FontError::Io(e.to_string()))?;
+
+        let mut chars = BTreeSet::new();
+        collect_chars(&self, family, &mut chars);
+        let font = subset(&data, chars)?;
+
+        let mut rule = String::from("@font-face { font-family: ");
+        push_css_string(&mut rule, family);
+        rule.push_str("; src: url(data:font/ttf;base64,");
+        STANDARD.encode_string(font, &mut rule);
+        rule.push_str(r#") format("truetype"); }"#);
+        self.kind.font_faces.push(rule);
+        Ok(self)
+    }
+}
+
+/// returns true if the family is in the css font-family list
+fn uses_family(list: &str, family: &str) -> bool {
+    list.split(',').any(|f| {
+        f.trim()
+            .trim_matches(['"', '\''])
+            .eq_ignore_ascii_case(family)
+    })
+}
+
+/// Walks the element tree and collects the characters of the text that renders with the family.
+/// Text without a font family on itself or an ancestor is collected as well.
+fn collect_chars(root: &impl Walk, family: &str, chars: &mut BTreeSet<char>) {
+    // the font family of the element at each depth of the current branch
+    let mut families: Vec<Option<String>> = vec![];
+    root.walk(|node, depth| {
+        families.truncate(depth);
+        let inherited = families.last().cloned().flatten();
+        let list = font_family(node).or(inherited);
+        if list.as_deref().is_none_or(|f| uses_family(f, family)) {
+            chars.extend(node.character_data().into_iter().flat_map(str::chars));
+        }
+        families.push(list);
+    });
+}
+
+/// The font family of the element, set with the typed style, a custom property or an
+/// attribute of a parsed element
+fn font_family(node: &dyn Node) -> Option<String> {
+    let style = node.get_style();
+    let property = |list: &[(String, String)]| {
+        list.iter()
+            .find(|(name, _)| name == "font-family")
+            .map(|(_, value)| value.clone())
+    };
+    style
+        .font_family
+        .clone()
+        .or_else(|| property(&style.kv))
+        .or_else(|| property(node.get_attrs()))
+}
+
+/// Appends the value quoted as a css string
+fn push_css_string(quoted: &mut String, value: &str) {
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            // a line break can't be in a string, it is escaped as a code point
+            '\n' | '\r' | '\x0c' => {
+                let _ = write!(quoted, "\\{:x} ", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parent, font::TEST_FONT};
+
+    use super::*;
+
+    #[test]
+    fn collects_text_with_family() {
+        let svg = Element::svg()
+            .push(
+                Element::text(0, 0)
+                    .font_family("Inter, sans-serif")
+                    .push("ab"),
+            )
+            .push(Element::text(0, 0).font_family("Mono").push("cd"))
+            .push(
+                Element::group().push(
+                    Element::text(0, 0)
+                        .push("e")
+                        .push(Element::tspan(0, 0).font_family("Mono").push("f"))
+                        .push(Element::generic("a").push("g"))
+                        .push(
+                            Element::generic("textPath")
+                                .attr("font-family", "Mono")
+                                .push("h"),
+                        ),
+                ),
+            )
+            .push(
+                Element::group()
+                    .style_kv("font-family", "Inter")
+                    .push(Element::text(0, 0).push("i")),
+            );
+        let mut chars = BTreeSet::new();
+        collect_chars(&svg, "inter", &mut chars);
+        assert_eq!(chars.into_iter().collect::<String>(), "abegi");
+    }
+
+    #[test]
+    fn quoted_family() {
+        let css_string = |value| {
+            let mut quoted = String::new();
+            push_css_string(&mut quoted, value);
+            quoted
+        };
+        assert_eq!(css_string("DejaVu Sans"), r#""DejaVu Sans""#);
+        assert_eq!(css_string("a\"}\\\n"), r#""a\"}\\\a ""#);
+
+        let svg = Element::svg()
+            .push(Element::text(0, 0).push("Hi"))
+            .embed_font("x\"} ]]> <", TEST_FONT)
+            .unwrap()
+            .render(None);
+        assert!(
+            svg.contains(
+                r#"<![CDATA[@font-face { font-family: "x\"} ]]]]><![CDATA[> <"; src: url("#
+            ),
+            "{svg}"
+        );
+    }
+
+    #[test]
+    fn embed_font() {
+        let svg = Element::svg()
+            .push(Element::text(0, 0).font_family("DejaVu Sans").push("Hi"))
+            .embed_font("DejaVu Sans", TEST_FONT)
+            .unwrap()
+            .render(None);
+        assert!(svg.contains(r#"<style>"#));
+        assert!(svg.contains(
+            r#"@font-face { font-family: "DejaVu Sans"; src: url(data:font/ttf;base64,"#
+        ));
+    }
+}
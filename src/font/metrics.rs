@@ -0,0 +1,239 @@
+use std::{collections::HashMap, fmt::Display, path::Path};
+
+use ttf_parser::Face;
+
+use crate::measure::Measurement;
+
+/// Advance widths, kerning and vertical metrics of a font, in font units.
+/// Can be loaded from a TrueType/OpenType file or taken from the built-in tables,
+/// see [`FontMetrics::builtin`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontMetrics {
+    pub(crate) units_per_em: u16,
+    pub(crate) ascent: i16,
+    pub(crate) descent: i16,
+    pub(crate) line_gap: i16,
+    pub(crate) advances: HashMap<char, u16>,
+    /// used for characters that are missing in the font
+    pub(crate) default_advance: u16,
+    pub(crate) kerning: HashMap<(char, char), i16>,
+}
+
+impl FontMetrics {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        let data = std::fs::read(path).map_err(|e| FontError::Io(e.to_string()))?;
+        Self::from_bytes(&data)
+    }
+
+    /// Parses the metrics from a TrueType/OpenType font.
+    /// NOTE: only pair kerning from the `kern` table is read, GPOS kerning is ignored.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, FontError> {
+        let face = Face::parse(data, 0).map_err(|e| FontError::Parse(e.to_string()))?;
+
+        let mut advances = HashMap::new();
+        let mut glyph_chars: HashMap<u16, Vec<char>> = HashMap::new();
+        if let Some(cmap) = face.tables().cmap {
+            for subtable in cmap.subtables.into_iter().filter(|s| s.is_unicode()) {
+                subtable.codepoints(|cp| {
+                    let Some(c) = char::from_u32(cp) else {
+                        return;
+                    };
+                    if let Some(glyph) = subtable.glyph_index(cp)
+                        && let Some(advance) = face.glyph_hor_advance(glyph)
+                    {
+                        advances.insert(c, advance);
+                        glyph_chars.entry(glyph.0).or_default().push(c);
+                    }
+                });
+            }
+        }
+
+        let mut kerning = HashMap::new();
+        if let Some(kern) = face.tables().kern {
+            for subtable in kern.subtables.into_iter().filter(|s| s.horizontal) {
+                let ttf_parser::kern::Format::Format0(table) = subtable.format else {
+                    continue;
+                };
+                for pair in table.pairs {
+                    let (Some(left), Some(right)) = (
+                        glyph_chars.get(&pair.left().0),
+                        glyph_chars.get(&pair.right().0),
+                    ) else {
+                        continue;
+                    };
+                    for l in left {
+                        for r in right {
+                            kerning.insert((*l, *r), pair.value);
+                        }
+                    }
+                }
+            }
+        }
+
+        let default_advance = face
+            .glyph_hor_advance(ttf_parser::GlyphId(0))
+            .unwrap_or(face.units_per_em() / 2);
+
+        Ok(Self {
+            units_per_em: face.units_per_em(),
+            ascent: face.ascender(),
+            descent: face.descender(),
+            line_gap: face.line_gap(),
+            advances,
+            default_advance,
+            kerning,
+        })
+    }
+
+    /// Advance width of a character in font units
+    pub fn advance(&self, c: char) -> u16 {
+        self.advances
+            .get(&c)
+            .copied()
+            .unwrap_or(self.default_advance)
+    }
+
+    /// Kerning adjustment between two characters in font units
+    pub fn kerning(&self, left: char, right: char) -> i16 {
+        self.kerning.get(&(left, right)).copied().unwrap_or(0)
+    }
+
+    /// Distance from the baseline to the top of the font at the given size
+    pub fn ascent(&self, size: f64) -> f64 {
+        self.scale(self.ascent, size)
+    }
+
+    /// Distance from the baseline to the bottom of the font at the given size, usually negative
+    pub fn descent(&self, size: f64) -> f64 {
+        self.scale(self.descent, size)
+    }
+
+    /// Default distance between two baselines at the given size
+    pub fn line_height(&self, size: f64) -> f64 {
+        self.scale(self.ascent - self.descent + self.line_gap, size)
+    }
+
+    /// Width of a single line of text at the given size, kerning included.
+    pub fn text_width(&self, text: &str, size: f64) -> f64 {
+        let mut units = 0_f64;
+        let mut prev = None;
+        for c in text.chars() {
+            if let Some(p) = prev {
+                units += self.kerning(p, c) as f64;
+            }
+            units += self.advance(c) as f64;
+            prev = Some(c);
+        }
+        units * size / self.units_per_em as f64
+    }
+
+    fn scale(&self, units: i16, size: f64) -> f64 {
+        units as f64 * size / self.units_per_em as f64
+    }
+}
+
+/// Measures the text, every line break starts a new line.
+/// The width is the width of the widest line and the height is the line height between the
+/// baselines plus the ascent of the first line and the descent of the last.
+pub fn measure_text(text: &str, font: &FontMetrics, size: f64) -> Measurement {
+    measure_lines(
+        text,
+        font.line_height(size),
+        font.ascent(size) - font.descent(size),
+        |line| font.text_width(line, size),
+    )
+}
+
+/// Measures the lines of the text, `height` is the distance from the ascent to the descent of a
+/// single line.
+pub(crate) fn measure_lines(
+    text: &str,
+    line_height: f64,
+    height: f64,
+    text_width: impl Fn(&str) -> f64,
+) -> Measurement {
+    let (lines, width) = text
+        .lines()
+        .fold((0, 0_f64), |(n, w), line| (n + 1, w.max(text_width(line))));
+    let lines = lines.max(1);
+    Measurement::from((width, line_height * (lines - 1) as f64 + height))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontError {
+    Io(String),
+    Parse(String),
+    Unsupported(String),
+}
+
+impl Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "could not read font: {e}"),
+            FontError::Parse(e) => write!(f, "could not parse font: {e}"),
+            FontError::Unsupported(e) => write!(f, "unsupported: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::font::Font;
+
+    use super::*;
+
+    #[test]
+    fn builtin_helvetica() {
+        let helvetica = FontMetrics::builtin("Helvetica").unwrap();
+        // "Hi" = 722 + 222 units
+        assert!((helvetica.text_width("Hi", 10.) - 9.44).abs() < 1e-9);
+        assert!((helvetica.ascent(10.) - 7.18).abs() < 1e-9);
+        assert!((helvetica.descent(10.) + 2.07).abs() < 1e-9);
+    }
+
+    #[test]
+    fn measure_lines() {
+        let courier = FontMetrics::builtin("Courier").unwrap();
+        let m = measure_text("abc\na", courier, 10.);
+        assert!((m.width() - 18.).abs() < 1e-9);
+        let line = courier.line_height(10.);
+        let expected = line + courier.ascent(10.) - courier.descent(10.);
+        assert!((m.height() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn font_measures_like_measure_text() {
+        let courier = FontMetrics::builtin("Courier").unwrap();
+        let font = Font::new("Courier", 10.);
+        assert!(Measurement::approx_eq(
+            font.measure("abc\na"),
+            measure_text("abc\na", courier, 10.)
+        ));
+
+        // one em per line without metrics
+        let unknown = Font::new("Unknown", 10.);
+        let m = unknown.measure("a\nb\nc");
+        assert!((m.height() - (2. * unknown.line_height() + 10.)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn from_file() {
+        let metrics = FontMetrics::from_file(crate::font::TEST_FONT).unwrap();
+        assert_eq!(metrics.units_per_em, 2048);
+        assert!(metrics.advance('W') > metrics.advance('i'));
+        assert!(
+            metrics.text_width("AV", 10.)
+                < metrics.text_width("A", 10.) + metrics.text_width("V", 10.)
+        );
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(matches!(
+            FontMetrics::from_file("does/not/exist.ttf"),
+            Err(FontError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,103 @@
+// ===== Font ==================================================================
+
+use std::borrow::Cow;
+
+pub use crate::font::metrics::{FontError, FontMetrics, measure_text};
+use crate::{font::metrics::measure_lines, measure::Measurement};
+
+mod builtin;
+mod embed;
+mod metrics;
+mod outline;
+pub mod subset;
+
+/// DejaVu Sans cut down to the printable ascii characters and é, used by the font tests.
+#[cfg(test)]
+pub(crate) const TEST_FONT: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/DejaVuSans-subset.ttf"
+);
+
+/// A font family at a given size, used to measure text for layout.
+/// The metrics are resolved from the built-in tables when the family is known, otherwise the
+/// widths are approximated, use [`Font::with_metrics`] to measure with a loaded font file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Font {
+    pub family: String,
+    pub size: f64,
+    metrics: Option<Cow<'static, FontMetrics>>,
+    /// the metrics are of another font, or there are none
+    approximate: bool,
+}
+
+impl Font {
+    pub fn new(family: &str, size: f64) -> Self {
+        let builtin = builtin::builtin_match(family);
+        Self {
+            family: family.to_string(),
+            size,
+            metrics: builtin.map(|(metrics, _)| Cow::Borrowed(metrics)),
+            approximate: !builtin.is_some_and(|(_, exact)| exact),
+        }
+    }
+
+    /// Returns true if the text is measured with the widths of another font, like Inter with
+    /// the built-in Helvetica widths, or with approximated widths of an unknown font.
+    pub fn is_approximate(&self) -> bool {
+        self.approximate
+    }
+
+    /// Uses the metrics of a loaded font instead of the built-in tables.
+    /// ```no_run
+    /// # use svg_maker::font::{Font, FontMetrics};
+    /// let metrics = FontMetrics::from_file("fonts/Inter-Regular.ttf").unwrap();
+    /// let font = Font::new("Inter", 12.).with_metrics(metrics);
+    /// ```
+    pub fn with_metrics(mut self, metrics: FontMetrics) -> Self {
+        self.metrics = Some(Cow::Owned(metrics));
+        self.approximate = false;
+        self
+    }
+
+    pub fn metrics(&self) -> Option<&FontMetrics> {
+        self.metrics.as_deref()
+    }
+
+    /// Returns the advance width of a single line of text.
+    pub fn text_width(&self, text: &str) -> f64 {
+        match self.metrics() {
+            Some(metrics) => metrics.text_width(text, self.size),
+            None => text.chars().map(approx_advance).sum::<f64>() * self.size,
+        }
+    }
+
+    /// Measures the text, see [`measure_text`]. Without metrics a line is one em high.
+    pub fn measure(&self, text: &str) -> Measurement {
+        let height = match self.metrics() {
+            Some(metrics) => metrics.ascent(self.size) - metrics.descent(self.size),
+            None => self.size,
+        };
+        measure_lines(text, self.line_height(), height, |line| {
+            self.text_width(line)
+        })
+    }
+
+    /// Default distance between two baselines
+    pub fn line_height(&self) -> f64 {
+        match self.metrics() {
+            Some(metrics) => metrics.line_height(self.size),
+            None => self.size * 1.2,
+        }
+    }
+}
+
+/// approximated advance width of a character in em units.
+fn approx_advance(c: char) -> f64 {
+    match c {
+        'i' | 'l' | 'j' | 't' | 'f' | 'r' | 'I' | '1' | ' ' => 0.3,
+        '.' | ',' | ':' | ';' | '!' | '|' | '\'' => 0.25,
+        'm' | 'w' | 'M' | 'W' => 0.85,
+        c if c.is_uppercase() => 0.68,
+        _ => 0.52,
+    }
+}
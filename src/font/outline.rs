@@ -0,0 +1,507 @@
+// ===== Text to outlines =======================================================
+//
+// Converts text into paths so that it renders the same without the font installed.
+// The glyphs are looked up with the cmap and positioned with the advances and the `kern` table,
+// no complex shaping (ligatures, GSUB) is done.
+
+use std::{any::Any, path::Path as FsPath};
+
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+use crate::{
+    Parent,
+    element::Element,
+    font::FontError,
+    shapes::{
+        group::Group,
+        path::{Command, Path},
+        text::Text,
+        tspan::Tspan,
+    },
+    units::{Coord, CubicArgs, Length, QuadraticArgs, TextAnchor},
+};
+
+/// css default font size
+const DEFAULT_FONT_SIZE: f64 = 16.;
+
+/// Collects the outline of a glyph as path commands, scaled from font units and flipped to the
+/// y down coordinate system of svg.
+struct GlyphOutline {
+    commands: Vec<Command>,
+    x: f64,
+    y: f64,
+    scale: f64,
+}
+
+impl GlyphOutline {
+    fn coord(&self, x: f32, y: f32) -> Coord {
+        Coord::from((
+            self.x + x as f64 * self.scale,
+            self.y - y as f64 * self.scale,
+        ))
+    }
+}
+
+impl OutlineBuilder for GlyphOutline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let c = self.coord(x, y);
+        self.commands.push(Command::MoveTo(c));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let c = self.coord(x, y);
+        self.commands.push(Command::Line(c));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let args = QuadraticArgs {
+            p1: self.coord(x1, y1),
+            end: self.coord(x, y),
+        };
+        self.commands.push(Command::QuadraticBezier(args));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let args = CubicArgs {
+            p1: self.coord(x1, y1),
+            p2: self.coord(x2, y2),
+            end: self.coord(x, y),
+        };
+        self.commands.push(Command::CubicBezier(args));
+    }
+
+    fn close(&mut self) {
+        self.commands.push(Command::ClosePath);
+    }
+}
+
+/// A character of the text and the positions set for it by the text or its tspans
+#[derive(Debug, Clone)]
+struct Char {
+    c: char,
+    x: Option<f64>,
+    y: Option<f64>,
+    dx: Option<f64>,
+    dy: Option<f64>,
+    font: Inherited,
+}
+
+/// The font size and text anchor of the closest text or tspan that sets them
+#[derive(Debug, Clone, Copy)]
+struct Inherited {
+    size: f64,
+    anchor: TextAnchor,
+}
+
+impl Inherited {
+    fn resolve(self, size: Option<&Length>, anchor: Option<TextAnchor>) -> Result<Self, FontError> {
+        let size = match size {
+            Some(Length::Px(px)) => px.0,
+            None => self.size,
+            Some(_) => {
+                return Err(FontError::Unsupported(
+                    "font-size must be in user units to outline text".to_string(),
+                ));
+            }
+        };
+        Ok(Self {
+            size,
+            anchor: anchor.unwrap_or(self.anchor),
+        })
+    }
+}
+
+/// Returns one of the positions of a character
+type Field = fn(&mut Char) -> &mut Option<f64>;
+
+/// The positioning attributes of a text or a tspan
+struct Positions<'a> {
+    x: &'a [Length],
+    y: &'a [Length],
+    dx: &'a [Length],
+    dy: &'a [Length],
+    rotate: Option<&'a Vec<f64>>,
+}
+
+impl<'a> From<&'a Text> for Positions<'a> {
+    fn from(text: &'a Text) -> Self {
+        Self {
+            x: &text.x,
+            y: &text.y,
+            dx: &text.dx,
+            dy: &text.dy,
+            rotate: text.rotate.as_ref(),
+        }
+    }
+}
+
+impl<'a> From<&'a Tspan> for Positions<'a> {
+    fn from(tspan: &'a Tspan) -> Self {
+        Self {
+            x: &tspan.x,
+            y: &tspan.y,
+            dx: &tspan.dx,
+            dy: &tspan.dy,
+            rotate: tspan.rotate.as_ref(),
+        }
+    }
+}
+
+impl Positions<'_> {
+    /// Sets the n:th value on the n:th character, unless a tspan closer to the character has set
+    /// it already.
+    fn apply(&self, chars: &mut [Char]) -> Result<(), FontError> {
+        if self.rotate.is_some() {
+            return Err(FontError::Unsupported(
+                "rotated glyphs can't be outlined".to_string(),
+            ));
+        }
+        let lists: [(&[Length], Field); 4] = [
+            (self.x, |c| &mut c.x),
+            (self.y, |c| &mut c.y),
+            (self.dx, |c| &mut c.dx),
+            (self.dy, |c| &mut c.dy),
+        ];
+        for (list, field) in lists {
+            for (c, length) in chars.iter_mut().zip(list) {
+                let value = field(c);
+                if value.is_none() {
+                    *value = Some(user_units(length)?);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn user_units(length: &Length) -> Result<f64, FontError> {
+    match length {
+        Length::Px(px) => Ok(px.0),
+        _ => Err(FontError::Unsupported(
+            "x, y, dx and dy must be in user units to outline text".to_string(),
+        )),
+    }
+}
+
+fn collect_chars(child: &dyn Any, font: Inherited, chars: &mut Vec<Char>) -> Result<(), FontError> {
+    let text = if let Some(s) = child.downcast_ref::<String>() {
+        s.as_str()
+    } else if let Some(s) = child.downcast_ref::<&'static str>() {
+        s
+    } else if let Some(tspan) = child.downcast_ref::<Element<Tspan>>() {
+        let font = font.resolve(tspan.style.font_size.as_ref(), tspan.kind.text_anchor)?;
+        let start = chars.len();
+        for child in tspan.children() {
+            collect_chars(child.as_any(), font, chars)?;
+        }
+        return Positions::from(&tspan.kind).apply(&mut chars[start..]);
+    } else {
+        return Ok(());
+    };
+    chars.extend(text.chars().map(|c| Char {
+        c,
+        x: None,
+        y: None,
+        dx: None,
+        dy: None,
+        font,
+    }));
+    Ok(())
+}
+
+/// A placed glyph
+struct Glyph {
+    id: GlyphId,
+    x: f64,
+    y: f64,
+    /// font units to user units
+    scale: f64,
+}
+
+/// Glyphs that are anchored together, from one absolute x to the next
+struct Chunk {
+    glyphs: Vec<Glyph>,
+    /// the anchor of the first character
+    anchor: TextAnchor,
+    start: f64,
+    end: f64,
+}
+
+/// Places the glyphs of the text and its tspans and returns the outline of each visible glyph.
+/// A character with an absolute x starts a new chunk of text that is anchored on its own.
+fn glyph_outlines(text: &Element<Text>, data: &[u8]) -> Result<Vec<Vec<Command>>, FontError> {
+    let face = Face::parse(data, 0).map_err(|e| FontError::Parse(e.to_string()))?;
+    let font = Inherited {
+        size: DEFAULT_FONT_SIZE,
+        anchor: TextAnchor::Start,
+    }
+    .resolve(text.style.font_size.as_ref(), Some(text.anchor()))?;
+
+    let mut chars = vec![];
+    for child in text.children() {
+        collect_chars(child.as_any(), font, &mut chars)?;
+    }
+    Positions::from(&text.kind).apply(&mut chars)?;
+
+    let units_per_em = face.units_per_em() as f64;
+    let ids = chars
+        .iter()
+        .map(|c| face.glyph_index(c.c).unwrap_or(GlyphId(0)))
+        .collect::<Vec<_>>();
+
+    let mut chunks: Vec<Chunk> = vec![];
+    let (mut pen_x, mut pen_y) = (0., 0.);
+    for (n, (c, id)) in chars.iter().zip(&ids).enumerate() {
+        if let Some(x) = c.x {
+            pen_x = x;
+        }
+        if let Some(y) = c.y {
+            pen_y = y;
+        }
+        pen_x += c.dx.unwrap_or(0.);
+        pen_y += c.dy.unwrap_or(0.);
+        if n == 0 || c.x.is_some() {
+            chunks.push(Chunk {
+                glyphs: vec![],
+                anchor: c.font.anchor,
+                start: pen_x,
+                end: pen_x,
+            });
+        }
+
+        // safe to unwrap, the first character starts a chunk
+        let chunk = chunks.last_mut().unwrap();
+        let scale = c.font.size / units_per_em;
+        chunk.glyphs.push(Glyph {
+            id: *id,
+            x: pen_x,
+            y: pen_y,
+            scale,
+        });
+        let kerning = match (chars.get(n + 1), ids.get(n + 1)) {
+            (Some(next), Some(next_id)) if next.x.is_none() => kerning(&face, *id, *next_id),
+            _ => 0,
+        };
+        pen_x += (face.glyph_hor_advance(*id).unwrap_or(0) as f64 + kerning as f64) * scale;
+        chunk.end = pen_x;
+    }
+
+    let mut outlines = vec![];
+    for chunk in chunks {
+        let width = chunk.end - chunk.start;
+        let shift = match chunk.anchor {
+            TextAnchor::Start => 0.,
+            TextAnchor::Middle => width / 2.,
+            TextAnchor::End => width,
+        };
+        for glyph in chunk.glyphs {
+            let mut outline = GlyphOutline {
+                commands: vec![],
+                x: glyph.x - shift,
+                y: glyph.y,
+                scale: glyph.scale,
+            };
+            if face.outline_glyph(glyph.id, &mut outline).is_some() && !outline.commands.is_empty()
+            {
+                outlines.push(outline.commands);
+            }
+        }
+    }
+    Ok(outlines)
+}
+
+fn kerning(face: &Face, left: GlyphId, right: GlyphId) -> i16 {
+    face.tables()
+        .kern
+        .and_then(|kern| {
+            kern.subtables
+                .into_iter()
+                .filter(|s| s.horizontal && !s.variable)
+                .find_map(|s| s.glyphs_kerning(left, right))
+        })
+        .unwrap_or(0)
+}
+
+fn read(font_file: &FsPath) -> Result<Vec<u8>, FontError> {
+    std::fs::read(font_file).map_err(|e| FontError::Io(e.to_string()))
+}
+
+impl Element<Text> {
+    /// Converts the text to a single path using the outlines of the font, the id, class, style
+    /// and transforms are kept on the path.
+    /// The glyphs are placed with the x, y, dx and dy, and sized and anchored with the font size
+    /// and text anchor of the text and its tspans, per glyph rotation is not supported.
+    /// ```no_run
+    /// # use svg_maker::{Parent, element::Element};
+    /// let logo = Element::text(0, 40)
+    ///     .font_size(32)
+    ///     .push("svg_maker")
+    ///     .to_path("fonts/Inter-Bold.ttf")
+    ///     .unwrap();
+    /// ```
+    pub fn to_path(self, font_file: impl AsRef<FsPath>) -> Result<Element<Path>, FontError> {
+        let data = read(font_file.as_ref())?;
+        self.to_path_from_bytes(&data)
+    }
+
+    /// Same as [`Element::to_path`] with the font already loaded.
+    pub fn to_path_from_bytes(self, font: &[u8]) -> Result<Element<Path>, FontError> {
+        let commands = glyph_outlines(&self, font)?.into_iter().flatten().collect();
+        let mut path = self.with_kind(Path { path: commands });
+        path.style.clear_text_properties();
+        Ok(path)
+    }
+
+    /// Converts the text to a group with one path per glyph, the id, class, style and transforms
+    /// are kept on the group.
+    pub fn to_glyph_paths(
+        self,
+        font_file: impl AsRef<FsPath>,
+    ) -> Result<Element<Group>, FontError> {
+        let data = read(font_file.as_ref())?;
+        let glyphs = glyph_outlines(&self, &data)?;
+        let mut group = self.with_kind(Group {});
+        group.style.clear_text_properties();
+        Ok(group.push_iter(glyphs.into_iter().map(|path| Element::new(Path { path }))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::{Options, font::TEST_FONT, units::TextAnchor};
+
+    use super::*;
+
+    #[test]
+    fn outline_builder_flips_y() {
+        let mut outline = GlyphOutline {
+            commands: vec![],
+            x: 10.,
+            y: 100.,
+            scale: 0.5,
+        };
+        outline.move_to(0., 0.);
+        outline.line_to(10., 20.);
+        outline.quad_to(20., 20., 20., 0.);
+        outline.close();
+
+        let path = Element::new(Path {
+            path: outline.commands,
+        });
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        assert_eq!(
+            path.render(Some(opts)),
+            r#"<path d="M10,100 L15,90 Q20,90 20,100 Z"/>"#
+        );
+    }
+
+    #[test]
+    fn text_to_path() {
+        let path = Element::text(10, 50)
+            .id("logo")
+            .font_size(20)
+            .font_family("DejaVu Sans")
+            .push("Hi")
+            .to_path(TEST_FONT)
+            .unwrap();
+        assert_eq!(path.id.as_deref(), Some("logo"));
+        let rendered = path.render(None);
+        assert!(rendered.starts_with(r#"<path id="logo" d="M"#));
+        assert!(rendered.contains('Z'));
+        assert!(!rendered.contains("font"));
+    }
+
+    #[test]
+    fn glyph_paths() {
+        // the space has no outline
+        let group = Element::text(0, 0)
+            .push("a b")
+            .to_glyph_paths(TEST_FONT)
+            .unwrap();
+        assert_eq!(group.len(), 2);
+    }
+
+    /// the path of the last glyph of the text
+    fn last_glyph(text: Element<Text>) -> String {
+        let group = text.to_glyph_paths(TEST_FONT).unwrap().render(None);
+        let start = group.rfind("<path").unwrap();
+        group[start..].lines().next().unwrap().to_string()
+    }
+
+    #[rstest]
+    #[case::positioned(Element::text_positioned([10, 50], [20]).push("ab"))]
+    #[case::relative(Element::text(40, 10).dx([10.]).dy([10.]).push("b"))]
+    #[case::nearest_wins(
+        Element::text_positioned([0, 0], [0, 20]).push("a").push(Element::tspan(50, 20).push("b"))
+    )]
+    #[case::tspan_dy(
+        Element::text(50, 0).push("a").push(Element::tspan_positioned([50], Vec::<Length>::new()).dy([20.]).push("b"))
+    )]
+    fn glyph_positions(#[case] text: Element<Text>) {
+        let expected = last_glyph(Element::text(50, 20).push("b"));
+        assert_eq!(last_glyph(text), expected);
+    }
+
+    #[test]
+    fn chunks_are_anchored_on_their_own() {
+        let text = Element::text(0, 0)
+            .text_anchor(TextAnchor::End)
+            .push("aaaa")
+            .push(Element::tspan(50, 20).push("b"));
+        let expected = Element::text(50, 20).text_anchor(TextAnchor::End).push("b");
+        assert_eq!(last_glyph(text), last_glyph(expected));
+    }
+
+    #[test]
+    fn tspans_set_their_size_and_anchor() {
+        let text = Element::text(0, 0).font_size(10).push("a").push(
+            Element::tspan(50, 20)
+                .font_size(20)
+                .text_anchor(TextAnchor::Middle)
+                .push("b"),
+        );
+        let expected = Element::text(50, 20)
+            .font_size(20)
+            .text_anchor(TextAnchor::Middle)
+            .push("b");
+        assert_eq!(last_glyph(text), last_glyph(expected.clone()));
+
+        // a nested tspan inherits them
+        let nested = Element::tspan_positioned(Vec::<Length>::new(), Vec::<Length>::new());
+        let text = Element::text(0, 0).push(
+            Element::tspan(50, 20)
+                .font_size(20)
+                .text_anchor(TextAnchor::Middle)
+                .push(nested.push("b")),
+        );
+        assert_eq!(last_glyph(text), last_glyph(expected));
+    }
+
+    #[test]
+    fn rotate_is_unsupported() {
+        let data = std::fs::read(TEST_FONT).unwrap();
+        let text = Element::text(0, 0).push(Element::tspan(0, 0).rotate(vec![45.]).push("a"));
+        assert!(matches!(
+            text.to_path_from_bytes(&data),
+            Err(FontError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn percent_position_is_unsupported() {
+        let text = Element::text(crate::units::Percent(50), 0).push("a");
+        assert!(matches!(
+            text.to_path_from_bytes(&[]),
+            Err(FontError::Parse(_))
+        ));
+        let text = Element::text(crate::units::Percent(50), 0).push("a");
+        let data = std::fs::read(TEST_FONT).unwrap();
+        assert!(matches!(
+            text.to_path_from_bytes(&data),
+            Err(FontError::Unsupported(_))
+        ));
+    }
+}
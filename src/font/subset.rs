@@ -0,0 +1,279 @@
+// ===== Subsetting =============================================================
+//
+// Glyph retaining subset of a TrueType font: the outlines of unused glyphs are removed from the
+// `glyf` table while every glyph keeps its id, so the cmap, hmtx and kerning tables stay valid
+// without being rewritten. Fonts with CFF outlines can't be subset.
+
+use std::collections::BTreeSet;
+
+use ttf_parser::Face;
+
+use crate::font::FontError;
+
+// composite glyph flags
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+struct TableRecord<'a> {
+    tag: [u8; 4],
+    data: &'a [u8],
+}
+
+/// Removes the outlines of every glyph that isn't needed to render the characters.
+pub fn subset(data: &[u8], chars: impl IntoIterator<Item = char>) -> Result<Vec<u8>, FontError> {
+    let face = Face::parse(data, 0).map_err(|e| FontError::Parse(e.to_string()))?;
+    if data.get(0..4) == Some(b"ttcf") {
+        return Err(FontError::Unsupported(
+            "font collections can't be subset".to_string(),
+        ));
+    }
+
+    let mut tables = read_tables(data)?;
+    let (Some(glyf), Some(loca), Some(head)) = (
+        find(&tables, b"glyf"),
+        find(&tables, b"loca"),
+        find(&tables, b"head"),
+    ) else {
+        return Err(FontError::Unsupported(
+            "only fonts with TrueType outlines can be subset".to_string(),
+        ));
+    };
+    let long_offsets = read_u16(head, 50)? == 1;
+    let num_glyphs = face.number_of_glyphs();
+
+    let offsets = (0..=num_glyphs as usize)
+        .map(|i| {
+            if long_offsets {
+                read_u32(loca, i * 4).map(|o| o as usize)
+            } else {
+                read_u16(loca, i * 2).map(|o| o as usize * 2)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let glyph = |id: u16| -> Result<&[u8], FontError> {
+        let (start, end) = (offsets[id as usize], offsets[id as usize + 1]);
+        glyf.get(start..end).ok_or_else(malformed)
+    };
+
+    // .notdef is always kept
+    let mut keep = BTreeSet::from([0_u16]);
+    let mut queue = chars
+        .into_iter()
+        .filter_map(|c| face.glyph_index(c))
+        .map(|g| g.0)
+        .collect::<Vec<_>>();
+    while let Some(id) = queue.pop() {
+        if id >= num_glyphs || !keep.insert(id) {
+            continue;
+        }
+        queue.extend(components(glyph(id)?)?);
+    }
+
+    let mut new_glyf = vec![];
+    let mut new_loca = vec![];
+    for id in 0..num_glyphs {
+        push_offset(&mut new_loca, new_glyf.len(), long_offsets);
+        if keep.contains(&id) {
+            new_glyf.extend_from_slice(glyph(id)?);
+            // short offsets needs the glyphs to be 2 byte aligned
+            if new_glyf.len() % 2 == 1 {
+                new_glyf.push(0);
+            }
+        }
+    }
+    push_offset(&mut new_loca, new_glyf.len(), long_offsets);
+
+    // the signature is invalid after the font is changed
+    tables.retain(|t| &t.tag != b"DSIG");
+    let sfnt_version = &data[0..4];
+    Ok(write_font(sfnt_version, &tables, &new_glyf, &new_loca))
+}
+
+/// returns the glyph ids of the components of a composite glyph
+fn components(glyph: &[u8]) -> Result<Vec<u16>, FontError> {
+    if glyph.is_empty() || (read_u16(glyph, 0)? as i16) >= 0 {
+        return Ok(vec![]);
+    }
+    let mut ids = vec![];
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        ids.push(read_u16(glyph, offset + 2)?);
+        offset += 4;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    Ok(ids)
+}
+
+fn push_offset(loca: &mut Vec<u8>, offset: usize, long_offsets: bool) {
+    if long_offsets {
+        loca.extend_from_slice(&(offset as u32).to_be_bytes());
+    } else {
+        loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes());
+    }
+}
+
+fn read_tables(data: &[u8]) -> Result<Vec<TableRecord<'_>>, FontError> {
+    let num_tables = read_u16(data, 4)? as usize;
+    (0..num_tables)
+        .map(|i| {
+            let record = 12 + i * 16;
+            let tag = data
+                .get(record..record + 4)
+                .ok_or_else(malformed)?
+                .try_into()
+                .map_err(|_| malformed())?;
+            let offset = read_u32(data, record + 8)? as usize;
+            let len = read_u32(data, record + 12)? as usize;
+            let data = data.get(offset..offset + len).ok_or_else(malformed)?;
+            Ok(TableRecord { tag, data })
+        })
+        .collect()
+}
+
+fn find<'a>(tables: &[TableRecord<'a>], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    tables.iter().find(|t| &t.tag == tag).map(|t| t.data)
+}
+
+fn write_font(sfnt_version: &[u8], tables: &[TableRecord], glyf: &[u8], loca: &[u8]) -> Vec<u8> {
+    let mut tables = tables
+        .iter()
+        .map(|t| {
+            let data = match &t.tag {
+                b"glyf" => glyf.to_vec(),
+                b"loca" => loca.to_vec(),
+                b"head" => {
+                    // checkSumAdjustment is recalculated when the font is written
+                    let mut head = t.data.to_vec();
+                    head[8..12].copy_from_slice(&[0; 4]);
+                    head
+                }
+                _ => t.data.to_vec(),
+            };
+            (t.tag, data)
+        })
+        .collect::<Vec<_>>();
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.leading_zeros() as u16;
+    let search_range: u16 = (1 << entry_selector) * 16;
+
+    let mut font = vec![];
+    font.extend_from_slice(sfnt_version);
+    font.extend_from_slice(&num_tables.to_be_bytes());
+    font.extend_from_slice(&search_range.to_be_bytes());
+    font.extend_from_slice(&entry_selector.to_be_bytes());
+    font.extend_from_slice(&(num_tables * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, data) in &tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        font.extend_from_slice(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBA_u32.wrapping_sub(checksum(&font));
+        font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0_u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, FontError> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(malformed)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, FontError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(malformed)
+}
+
+fn malformed() -> FontError {
+    FontError::Parse("malformed font table".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::font::TEST_FONT;
+
+    use super::*;
+
+    #[test]
+    fn subset_keeps_used_glyphs() {
+        let data = std::fs::read(TEST_FONT).unwrap();
+        let subset = subset(&data, "Hié".chars()).unwrap();
+        let glyf = |font| find(&read_tables(font).unwrap(), b"glyf").unwrap().len();
+        assert!(glyf(&subset) < glyf(&data) / 4);
+
+        let face = Face::parse(&subset, 0).unwrap();
+        let has_outline = |c| {
+            let id = face.glyph_index(c).unwrap();
+            face.glyph_bounding_box(id).is_some()
+        };
+        assert!(has_outline('H'));
+        // é is a composite glyph in DejaVu, its components must be kept
+        assert!(has_outline('é'));
+        assert!(!has_outline('x'));
+        assert_eq!(checksum(&subset), 0xB1B0_AFBA);
+    }
+
+    #[test]
+    fn cff_is_unsupported() {
+        // a font without a glyf table, like the fonts with CFF outlines
+        let mut data = std::fs::read(TEST_FONT).unwrap();
+        let num_tables = u16::from_be_bytes([data[4], data[5]]) as usize;
+        let record = (0..num_tables)
+            .map(|i| 12 + i * 16)
+            .find(|r| &data[*r..r + 4] == b"glyf")
+            .unwrap();
+        data[record..record + 4].copy_from_slice(b"glyx");
+        assert!(matches!(
+            subset(&data, "Hi".chars()),
+            Err(FontError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn checksum_pads_the_last_word() {
+        assert_eq!(checksum(&[0, 0, 0, 1, 1]), 1 + 0x0100_0000);
+    }
+}
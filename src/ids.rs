@@ -0,0 +1,297 @@
+// ===== Ids ====================================================================
+//
+// Unique ids for definitions and scoping of the ids of a document, so that several documents
+// can be inlined in the same html page without their ids colliding.
+
+use std::collections::HashSet;
+
+use crate::{
+    color::Color,
+    element::Element,
+    marker_traits::{BaseElement, ChildOf, ElementKind},
+    node::{Node, Walk},
+    shapes::svg::Svg,
+    visit::Visit,
+};
+
+/// Attributes that holds a space separated list of ids
+const ID_LIST_ATTRS: [&str; 8] = [
+    "aria-activedescendant",
+    "aria-controls",
+    "aria-describedby",
+    "aria-details",
+    "aria-errormessage",
+    "aria-flowto",
+    "aria-labelledby",
+    "aria-owns",
+];
+
+impl Element<Svg> {
+    /// Returns an id starting with the prefix that isn't used by any element of the document.
+    /// ```
+    /// # use svg_maker::{Parent, element::Element};
+    /// let mut svg = Element::svg().push(Element::rect(0, 0, 1, 1).id("clip-1"));
+    /// assert_eq!(svg.auto_id("clip"), "clip-2");
+    /// assert_eq!(svg.auto_id("clip"), "clip-3");
+    /// ```
+    pub fn auto_id(&mut self, prefix: &str) -> String {
+        let mut ids = HashSet::new();
+        collect_ids(self, &mut ids);
+        loop {
+            self.kind.next_id += 1;
+            let id = format!("{prefix}-{}", self.kind.next_id);
+            if !ids.contains(&id) {
+                return id;
+            }
+        }
+    }
+
+    /// Adds the definition with a unique id, the id is returned to reference the definition.
+    /// The tag of the element is used as the prefix of the id.
+    /// ```
+    /// # use svg_maker::{Parent, color::Color, element::Element};
+    /// let mut svg = Element::svg();
+    /// let dot = svg.def_auto_id(Element::circle(0, 0, 1));
+    /// let svg = svg.push(Element::use_href(10, 10).href(&dot));
+    /// assert!(svg.render(None).contains(r##"<use x="10" y="10" href="#circle-1"/>"##));
+    /// ```
+    pub fn def_auto_id<E>(&mut self, mut el: Element<E>) -> String
+    where
+        Element<E>: ChildOf<Svg> + BaseElement,
+        E: ElementKind + Visit + 'static,
+    {
+        let id = self.auto_id(el.kind.tag());
+        el.id = Some(id.clone());
+        self.kind.defs.push(Box::new(el));
+        id
+    }
+}
+
+impl<T: ElementKind + Visit + 'static> Element<T> {
+    /// Prefixes every id of the document with `{prefix}-` and updates the references to them:
+    /// hrefs, `url(#id)` in paints, styles and attributes like `clip-path`, the aria
+    /// attributes that holds ids, and `url(#id)` and `#id` selectors in the css of the svg.
+    /// References to ids that aren't in the document are kept.
+    /// ```
+    /// # use svg_maker::{Parent, element::Element};
+    /// let svg = Element::svg()
+    ///     .def(Element::circle(0, 0, 1).id("dot"))
+    ///     .push(Element::use_href(0, 0).href("dot"))
+    ///     .namespace_ids("chart1");
+    /// assert!(svg.render(None).contains(r##"href="#chart1-dot""##));
+    /// ```
+    pub fn namespace_ids(mut self, prefix: &str) -> Self {
+        let mut ids = HashSet::new();
+        collect_ids(&self, &mut ids);
+        let rename = |id: &str| ids.contains(id).then(|| format!("{prefix}-{id}"));
+        self.walk_mut(|node, _| rename_ids(node, &rename));
+        self
+    }
+}
+
+fn collect_ids(node: &impl Walk, ids: &mut HashSet<String>) {
+    node.walk(|node, _| ids.extend(node.get_id().map(String::from)));
+}
+
+/// Renames the id and the references of the element, `rename` returns `None` for ids that are
+/// kept as is.
+fn rename_ids(node: &mut dyn Node, rename: &dyn Fn(&str) -> Option<String>) {
+    let id = node.id_mut();
+    if let Some(new) = id.as_deref().and_then(rename) {
+        *id = Some(new);
+    }
+    if let Some(href) = node.href_mut() {
+        rename_href(href, rename);
+    }
+
+    let style = node.style_mut();
+    for color in [&mut style.fill, &mut style.stroke].into_iter().flatten() {
+        if let Color::Url(target) = color
+            && let Some(new) = rename(target.trim_start_matches('#'))
+        {
+            *target = new;
+        }
+    }
+    for (_, value) in &mut style.kv {
+        rename_urls(value, rename);
+    }
+
+    for (name, value) in node.attrs_mut() {
+        if matches!(name.as_str(), "href" | "xlink:href") {
+            rename_href(value, rename);
+        } else if ID_LIST_ATTRS.contains(&name.as_str()) {
+            *value = value
+                .split_whitespace()
+                .map(|id| rename(id).unwrap_or_else(|| id.to_string()))
+                .collect::<Vec<_>>()
+                .join(" ");
+        } else {
+            rename_urls(value, rename);
+        }
+    }
+
+    if let Some(svg) = node.downcast_mut::<Element<Svg>>()
+        && let Some(css) = &mut svg.kind.css
+    {
+        rename_urls(css, rename);
+        rename_selectors(css, rename);
+    }
+}
+
+fn rename_href(href: &mut String, rename: &dyn Fn(&str) -> Option<String>) {
+    if let Some(new) = href.strip_prefix('#').and_then(rename) {
+        *href = format!("#{new}");
+    }
+}
+
+/// Renames the ids of the `url(#id)` references in the value
+fn rename_urls(value: &mut String, rename: &dyn Fn(&str) -> Option<String>) {
+    if !value.contains("url(") {
+        return;
+    }
+    let mut renamed = String::with_capacity(value.len());
+    let mut rest = value.as_str();
+    while let Some(start) = rest.find("url(") {
+        let (before, url) = rest.split_at(start + "url(".len());
+        renamed.push_str(before);
+        let end = url.find(')').unwrap_or(url.len());
+        let target = url[..end].trim().trim_matches(['"', '\'']);
+        match target.strip_prefix('#').and_then(rename) {
+            Some(new) => {
+                renamed.push('#');
+                renamed.push_str(&new);
+            }
+            None => renamed.push_str(&url[..end]),
+        }
+        rest = &url[end..];
+    }
+    renamed.push_str(rest);
+    *value = renamed;
+}
+
+/// Renames the `#id` selectors of the css, hex colors in the declarations are skipped.
+fn rename_selectors(css: &mut String, rename: &dyn Fn(&str) -> Option<String>) {
+    if !css.contains('#') {
+        return;
+    }
+    let mut renamed = String::with_capacity(css.len());
+    // the open blocks, true for the blocks of at-rules like @media that holds rules
+    let mut blocks: Vec<bool> = vec![];
+    let mut prelude = 0;
+    let mut i = 0;
+    while let Some(c) = css[i..].chars().next() {
+        renamed.push(c);
+        i += c.len_utf8();
+        match c {
+            '{' => {
+                let at_rule = css[prelude..i].trim_start();
+                blocks.push(
+                    ["@media", "@supports", "@container", "@layer"]
+                        .iter()
+                        .any(|rule| at_rule.starts_with(rule)),
+                );
+                prelude = i;
+            }
+            '}' | ';' => {
+                if c == '}' {
+                    blocks.pop();
+                }
+                prelude = i;
+            }
+            '#' if blocks.last().copied().unwrap_or(true) => {
+                let end = css[i..]
+                    .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_')))
+                    .map_or(css.len(), |end| i + end);
+                if let Some(new) = rename(&css[i..end]) {
+                    renamed.push_str(&new);
+                    i = end;
+                }
+            }
+            _ => {}
+        }
+    }
+    *css = renamed;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Options, Parent};
+
+    use super::*;
+
+    #[test]
+    fn namespace_ids() {
+        let svg = Element::svg()
+            .def(Element::generic("linearGradient").id("fade"))
+            .def(Element::generic("clipPath").id("clip"))
+            .push(Element::generic("title").id("title").push("Revenue"))
+            .push(
+                Element::group()
+                    .attr("aria-labelledby", "title other")
+                    .attr("clip-path", "url(#clip)")
+                    .push(
+                        Element::rect(0, 0, 1, 1)
+                            .fill(Color::Url("fade".to_string()))
+                            .stroke(Color::Url("#external".to_string()))
+                            .style_kv("mask", "url('#clip')"),
+                    )
+                    .push(Element::use_href(0, 0).href("title")),
+            )
+            .namespace_ids("a");
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        let rendered = svg.render(Some(opts));
+        for expected in [
+            r#"<linearGradient id="a-fade"/>"#,
+            r#"<clipPath id="a-clip"/>"#,
+            r#"<title id="a-title">"#,
+            r#"aria-labelledby="a-title other" clip-path="url(#a-clip)""#,
+            r#"style="fill: url(#a-fade); stroke: url(#external); mask:url(#a-clip);""#,
+            r##"href="#a-title""##,
+        ] {
+            assert!(rendered.contains(expected), "{expected} in {rendered}");
+        }
+        assert!(
+            svg.check_references()
+                .iter()
+                .all(|d| d.message.contains("external"))
+        );
+    }
+
+    #[test]
+    fn css_references() {
+        let svg = Element::svg()
+            .css(
+                "rect { fill: url(#fade); stroke: #fade; }\n\
+                 @media print { #bar, .x #bar:hover { fill: url('#fade') } }",
+            )
+            .def(Element::generic("linearGradient").id("fade"))
+            .push(Element::rect(0, 0, 1, 1).id("bar"))
+            .namespace_ids("a");
+
+        let rendered = svg.render(None);
+        assert!(
+            rendered.contains(
+                "rect { fill: url(#a-fade); stroke: #fade; }\n\
+                 @media print { #a-bar, .x #a-bar:hover { fill: url(#a-fade) } }"
+            ),
+            "{rendered}"
+        );
+        assert!(svg.check_references().is_empty());
+        let mut opts = Options::default();
+        opts.optimizations.remove_unused_defs = true;
+        assert!(svg.render(Some(opts)).contains(r#"id="a-fade""#));
+    }
+
+    #[test]
+    fn auto_ids() {
+        let mut svg =
+            Element::svg().push(Element::group().push(Element::circle(0, 0, 1).id("circle-1")));
+        let first = svg.def_auto_id(Element::circle(0, 0, 1));
+        let second = svg.def_auto_id(Element::circle(0, 0, 1));
+        assert_eq!(first, "circle-2");
+        assert_eq!(second, "circle-3");
+    }
+}
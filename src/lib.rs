@@ -5,24 +5,42 @@ use svg_maker_derive::*;
 use crate::{
     buffer::Buffer,
     shapes::{
-        circle::Circle, foreign_object::ForeignObject, group::Group, line::Line, path::Path,
-        polygon::Polygon, rect::Rect, svg::Svg, text::Text, tspan::Tspan, use_href::Use,
+        circle::Circle, ellipse::Ellipse, foreign_object::ForeignObject, generic::Generic,
+        group::Group, line::Line, path::Path, polygon::Polygon, polyline::Polyline, rect::Rect,
+        svg::Svg, text::Text, tspan::Tspan, use_href::Use,
     },
     units::{AlignAspectRatio, MeetOrSlice},
     visit::Visit,
 };
 
 pub use crate::marker_traits::*;
+pub use diff::diff;
+#[cfg(feature = "serde")]
+pub use serialization::Adopt;
 
 pub mod animations;
 mod buffer;
 pub mod color;
+pub mod diagnostics;
+pub mod diff;
 pub mod element;
+pub mod font;
+mod ids;
 pub mod marker_traits;
-mod measure;
+pub mod measure;
+pub mod node;
+pub mod parse;
 mod path_parser;
+#[cfg(feature = "raster")]
+pub mod raster;
+pub mod select;
+#[cfg(feature = "serde")]
+mod serialization;
 pub mod shapes;
 pub mod style;
+pub mod tester;
+pub mod testing;
+pub mod text_layout;
 pub mod units;
 pub mod visit;
 
@@ -39,7 +57,7 @@ pub mod visit;
 #[macro_export]
 macro_rules! impl_parent_of {
     ($parent:ty, $($child:ty),+ ) => {
-        $(impl $crate::marker_traits::ChildOf<$parent> for $crate::element::Element<$child> {} )+
+        $crate::impl_parent_child!($parent, $($child),+);
     };
 }
 
@@ -56,7 +74,7 @@ macro_rules! impl_parent_of {
 #[macro_export]
 macro_rules! impl_child_of {
     ($child:ty, $($parent:ty),+ ) => {
-        $(impl $crate::marker_traits::ChildOf<$parent> for $crate::element::Element<$child> {} )+
+        $( $crate::impl_parent_child!($parent, $child); )+
     };
 }
 
@@ -73,6 +91,11 @@ macro_rules! impl_parent_child {
             fn get_id(&self) -> Option<&str> {
                 self.id.as_ref().map(|s| s.as_str())
             }
+            fn as_node(&self) -> Option<&dyn $crate::node::Node> { Some(self) }
+            fn as_node_mut(&mut self) -> Option<&mut dyn $crate::node::Node> { Some(self) }
+            fn clone_box(&self) -> Box<dyn $crate::marker_traits::ChildOf<$parent>> {
+                Box::new(self.clone())
+            }
         }
         // impl $crate::marker_traits::ParentOf<$crate::element::Element<$child>> for $parent { }
         )+
@@ -80,33 +103,100 @@ macro_rules! impl_parent_child {
     };
 }
 
-impl_parent_child!(
-    Svg,
-    Use,
-    Line,
-    Rect,
-    Circle,
-    Text,
-    Polygon,
-    Path,
-    Group,
-    ForeignObject
-);
-impl_parent_child!(
-    Group,
-    Group,
-    Use,
-    Line,
-    Rect,
-    Circle,
-    Text,
-    Polygon,
-    Path,
-    ForeignObject
-);
-impl_parent_child!(Text, Tspan, String);
+/// character data (naked strings and [`Raw`] markup) is not an `Element<T>`, so it has no id or
+/// z index.
+/// ```impl_character_data_of(<PARENT>, <PARENT>, ...); ```
+macro_rules! impl_character_data_of {
+    ($($parent:ty),+ ) => {
+        $(
+        impl_character_data_of!(@data $parent, String, &'static str, $crate::Raw);
+        )+
+    };
+    (@data $parent:ty, $($data:ty),+) => {
+        $(
+        impl $crate::marker_traits::ChildOf<$parent> for $data {
+            fn as_any(&self) -> &dyn std::any::Any { self }
+            fn as_any_mut(&mut self) -> &mut dyn std::any::Any { self }
+            fn get_z_index(&self) -> Option<i32> {
+                None
+            }
+            fn get_id(&self) -> Option<&str> {
+                None
+            }
+            fn as_node(&self) -> Option<&dyn $crate::node::Node> { None }
+            fn as_node_mut(&mut self) -> Option<&mut dyn $crate::node::Node> { None }
+            fn clone_box(&self) -> Box<dyn $crate::marker_traits::ChildOf<$parent>> {
+                Box::new(self.clone())
+            }
+        }
+        )+
+    };
+}
+
+// ===== Content model ========================================================
+//
+// Permitted children of every implemented element kind, see categories.md and
+// https://www.w3.org/TR/SVG2/struct.html for the full content model.
+// An element that doesn't list a child can't hold it at compile time.
+//
+// Container elements: <svg>, <g>
+// Renderable elements: everything except <tspan> that is implemented.
+// Text content child elements: <tspan>, character data
+// Generic elements can be both parent and child of anything, the content model of the
+// elements it stands in for (<defs>, <clipPath>, <a>, <textPath>..) isn't known.
+//
+// The model is written once, `content_model!` passes it to the macro that implements
+// `ChildOf` below, and to the macros of the parser and of serde that adopt parsed children.
+// Every kind is listed, `; CharacterData` marks the parents of character data.
+
+/// Calls the macro with the content model:
+/// `Parent: [Child, Child, ..; CharacterData]` for every element kind
+#[rustfmt::skip]
+macro_rules! content_model {
+    ($callback:ident) => {
+        $callback! {
+            // Container elements
+            Svg: [Svg, Group, Use, Circle, Ellipse, Line, Path, Polygon, Polyline, Rect,
+                ForeignObject, Text, Generic],
+            Group: [Svg, Group, Use, Circle, Ellipse, Line, Path, Polygon, Polyline, Rect,
+                ForeignObject, Text, Generic],
+            // Text content elements
+            Text: [Tspan, Generic; CharacterData],
+            Tspan: [Tspan, Generic; CharacterData],
+            ForeignObject: [; CharacterData],
+            // Unknown elements
+            Generic: [Svg, Group, Use, Circle, Ellipse, Line, Path, Polygon, Polyline, Rect,
+                ForeignObject, Text, Tspan, Generic; CharacterData],
+            // Leaf elements
+            Use: [],
+            Circle: [],
+            Ellipse: [],
+            Line: [],
+            Path: [],
+            Polygon: [],
+            Polyline: [],
+            Rect: [],
+        }
+    };
+}
+pub(crate) use content_model;
+
+macro_rules! impl_content_model {
+    ($($parent:ident: [$($child:ident),* $(; $data:ident)?]),+ $(,)?) => {
+        $(
+        $( impl_parent_child!($parent, $child); )*
+        $( impl_content_model!(@$data $parent); )?
+        )+
+    };
+    (@CharacterData $parent:ident) => {
+        impl_character_data_of!($parent);
+    };
+}
+
+content_model!(impl_content_model);
 
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Viewbox {
     x: f64,
     y: f64,
@@ -117,16 +207,38 @@ struct Viewbox {
 impl Visit for Viewbox {
     fn visit(&self, buffer: &mut Buffer) {
         let Viewbox { x, y, w, h } = self;
-        buffer.push_str(&format!("{} {} {} {}", x, y, w, h));
+        for (i, v) in [x, y, w, h].into_iter().enumerate() {
+            if i > 0 {
+                buffer.push_space();
+            }
+            buffer.push_number(*v);
+        }
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Options {
     pub invert_y: bool,
+    /// Number of decimals every number is rounded to, `None` writes the numbers as is.
+    /// Defaults to 3.
+    pub precision: Option<u8>,
+    /// Sorts the attributes of every tag and the declarations of the style attribute by name,
+    /// for output that diffs well. By default they are rendered in the order they are set.
+    pub sort_attributes: bool,
     pub optimizations: Optimizations,
 }
 
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            invert_y: false,
+            precision: Some(3),
+            sort_attributes: false,
+            optimizations: Optimizations::default(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Optimizations {
     pub remove_unit_for_px: bool,
@@ -138,9 +250,19 @@ pub struct Optimizations {
     /// # Example
     /// d="L10,20 L30,40" => d="L10,20,30,40"
     pub collapse_same_path_command: bool,
+    /// writes numbers in their shortest form, without the leading zero or in exponent form
+    /// # Example
+    /// 0.5 => .5, -0.5 => -.5, 1000 => 1e3, 0.0001 => 1e-4
+    pub minify_numbers: bool,
+    /// removes the definitions of the svg that aren't referenced by `url(#id)` or a href.
+    /// Definitions used from outside the document, like the css of the html page or another
+    /// inlined svg, are removed as well, so it is not part of [`Optimizations::all`].
+    pub remove_unused_defs: bool,
 }
 
 impl Optimizations {
+    /// Every optimization that keeps the rendered document the same,
+    /// `remove_unused_defs` is opt-in.
     pub fn all() -> Self {
         Self {
             remove_unit_for_px: true,
@@ -149,6 +271,8 @@ impl Optimizations {
             remove_newline: true,
             remove_indent: true,
             collapse_same_path_command: true,
+            minify_numbers: true,
+            remove_unused_defs: false,
         }
     }
 }
@@ -162,6 +286,8 @@ impl Default for Optimizations {
     /// remove_newline: false,
     /// remove_indent: false,
     /// collapse_same_path_command: false,
+    /// minify_numbers: false,
+    /// remove_unused_defs: false,
     /// ```
     fn default() -> Self {
         Self {
@@ -171,24 +297,43 @@ impl Default for Optimizations {
             remove_newline: false,
             remove_indent: false,
             collapse_same_path_command: false,
+            minify_numbers: false,
+            remove_unused_defs: false,
         }
     }
 }
 
 // Raw ======================================
 
-#[derive(BaseStyle)]
+/// Trusted markup that is rendered without escaping, like the html content of a
+/// `<foreignObject>`. Text pushed as a `String` is escaped instead.
+/// ```
+/// # use svg_maker::{Parent, Raw, element::Element};
+/// let fo = Element::foreign_object(0, 0).push(Raw::new("<p>a &amp; b</p>"));
+/// assert!(fo.render(None).contains("<p>a &amp; b</p>"));
+/// ```
+#[derive(Debug, Clone, BaseStyle)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Raw {
     inner: String,
 }
 
+impl Raw {
+    pub fn new(markup: impl Into<String>) -> Self {
+        Self {
+            inner: markup.into(),
+        }
+    }
+}
+
 impl Visit for Raw {
     fn visit(&self, buffer: &mut Buffer) {
         buffer.push_str(&self.inner);
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct PreserveAspectRatio {
     alignment: AlignAspectRatio,
     meet_or_slice: MeetOrSlice,
@@ -201,18 +346,12 @@ impl Visit for PreserveAspectRatio {
     }
 }
 
-mod tester {
-    use crate::shapes::svg::Svg;
-
-    /// renders the svgs to a html page
-    fn run(svgs: &[Svg]) {
-        // TODO: render the svgs in a grid
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use crate::{element::Element, shapes::path::Path};
+    use crate::{
+        element::Element,
+        shapes::{path::Path, tspan::Tspan},
+    };
 
     use super::*;
 
@@ -226,4 +365,34 @@ mod tests {
         let path = s.get_element_by_id_mut::<Element<Path>>("this_id_doesnt_exist");
         assert!(path.is_none());
     }
+
+    #[test]
+    fn get_element_by_id_skips_character_data() {
+        let mut t = Element::tspan(1, 2)
+            .push("test".to_string())
+            .push(Element::tspan(3, 4).id("inner"));
+        assert!(t.get_element_by_id_mut::<Element<Tspan>>("inner").is_some());
+        assert!(
+            t.get_element_by_id_mut::<Element<Tspan>>("missing")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn content_model() {
+        let g = Element::group()
+            .push(Element::polyline().add_point((1, 1)))
+            .push(Element::ellipse(1, 2, 3, 4));
+        let s = Element::svg()
+            .push(Element::polyline().add_point((1, 1)))
+            .push(Element::ellipse(1, 2, 3, 4))
+            .push(Element::svg())
+            .push(g);
+        assert_eq!(s.len(), 4);
+
+        let t = Element::text(0, 0)
+            .push("a")
+            .push(Element::tspan(0, 0).push("b").push(Element::tspan(0, 0)));
+        assert_eq!(t.len(), 2);
+    }
 }
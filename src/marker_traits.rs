@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 use std::{any::Any, fmt::Debug};
 
-use crate::{Visit, element::Element};
+use crate::{Visit, buffer::Buffer, element::Element, node::Node};
 
 /// maker trait fro elements that can hava a style attribute.
 /// implementors of this trait will get access to the base style attributes.
@@ -47,6 +47,32 @@ where
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn get_z_index(&self) -> Option<i32>;
     fn get_id(&self) -> Option<&str>;
+    /// The child as an element of any kind, `None` for character data
+    fn as_node(&self) -> Option<&dyn Node>;
+    fn as_node_mut(&mut self) -> Option<&mut dyn Node>;
+    /// Clones the child behind the box, see the `Clone` impl of `Box<dyn ChildOf<T>>`
+    fn clone_box(&self) -> Box<dyn ChildOf<T>>;
+}
+
+impl<T: 'static> dyn ChildOf<T> {
+    /// Returns true if the child is of type `U`
+    pub fn is<U: ChildOf<T>>(&self) -> bool {
+        self.as_any().is::<U>()
+    }
+
+    pub fn downcast_ref<U: ChildOf<T>>(&self) -> Option<&U> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<U: ChildOf<T>>(&mut self) -> Option<&mut U> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+impl<T: 'static> Clone for Box<dyn ChildOf<T>> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
 }
 
 pub trait Parent<P>
@@ -69,6 +95,44 @@ pub trait RootElement {}
 
 pub trait ElementKind: Visit {
     const TAG: &'static str;
+
+    /// The tag name of the element, kinds with a tag that is only known at runtime overrides this.
+    fn tag(&self) -> &str {
+        Self::TAG
+    }
+
+    /// Attributes that the kind stores itself instead of on the element, see
+    /// [`Element::attr`].
+    fn attrs(&self) -> Option<&[(String, String)]> {
+        None
+    }
+
+    fn attrs_mut(&mut self) -> Option<&mut Vec<(String, String)>> {
+        None
+    }
+
+    /// Reference to another element, like the href of `<use>`
+    fn href_mut(&mut self) -> Option<&mut String> {
+        None
+    }
+
+    /// Elements that the kind holds besides the children, like the definitions of an svg.
+    fn nodes(&self) -> Vec<&dyn Node> {
+        vec![]
+    }
+
+    fn nodes_mut(&mut self) -> Vec<&mut dyn Node> {
+        vec![]
+    }
+
+    /// Returns true if the kind renders content of its own between the start tag and the
+    /// children, see [`ElementKind::visit_content`].
+    fn has_content(&self) -> bool {
+        false
+    }
+
+    /// Renders content that is not a child element, like the <style> and <defs> of an svg.
+    fn visit_content(&self, _buffer: &mut Buffer) {}
 }
 
 impl ElementKind for String {
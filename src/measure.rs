@@ -1,6 +1,6 @@
 // ===== Measurment ============================================================
 
-use std::ops::{Add, Deref};
+use std::ops::Add;
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Measurement {
@@ -9,6 +9,14 @@ pub struct Measurement {
 }
 
 impl Measurement {
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
     pub fn approx_eq(a: Self, b: Self) -> bool {
         const EPS: f64 = 1e-12;
         (a.width - b.width).abs() < EPS && (a.height - b.height).abs() < EPS
@@ -35,20 +43,12 @@ impl Add for Measurement {
     }
 }
 
-impl Deref for Measurement {
-    type Target = Measurement;
-
-    fn deref(&self) -> &Self::Target {
-        self
-    }
-}
-
 pub fn check_relative_path(
     max: &mut Measurement,
     current: &mut Measurement,
     value: impl Into<Measurement>,
 ) {
-    let t = *current + *value.into();
+    let t = *current + value.into();
     *current = t;
     if current.width.abs() >= max.width {
         max.width = current.width
@@ -0,0 +1,145 @@
+// ===== Nodes ==================================================================
+//
+// `Element<T>` is generic over its kind and the children are `Box<dyn ChildOf<T>>`, so a tree
+// can't be walked without knowing the kind of every element. `Node` is the part of an element
+// that doesn't depend on the kind, character data is not a node.
+
+use crate::{element::Transform, marker_traits::BaseElement, style::Style};
+
+/// An element of any kind, see [`ChildOf::as_node`](crate::marker_traits::ChildOf::as_node).
+pub trait Node: BaseElement {
+    fn tag(&self) -> &str;
+    fn get_id(&self) -> Option<&str>;
+    fn id_mut(&mut self) -> &mut Option<String>;
+    fn get_class(&self) -> Option<&str>;
+    fn class_mut(&mut self) -> &mut Option<String>;
+    fn get_style(&self) -> &Style;
+    fn style_mut(&mut self) -> &mut Style;
+    fn get_transforms(&self) -> &[Transform];
+    fn transforms_mut(&mut self) -> &mut Option<Vec<Transform>>;
+    /// Attributes that aren't modeled by the element, see [`Element::attr`](crate::element::Element::attr)
+    fn get_attrs(&self) -> &[(String, String)];
+    fn attrs_mut(&mut self) -> &mut Vec<(String, String)>;
+    /// Every attribute of the start tag as it is rendered, the modeled ones like the `width` of
+    /// a rect included
+    fn attributes(&self) -> Vec<(String, String)>;
+    /// The reference to another element of elements like `<use>`
+    fn href_mut(&mut self) -> Option<&mut String>;
+    /// The character data children, without the text of the child elements and raw markup
+    fn character_data(&self) -> Vec<&str>;
+    /// The child elements, and the definitions of an svg
+    fn child_nodes(&self) -> Vec<&dyn Node>;
+    fn child_nodes_mut(&mut self) -> Vec<&mut dyn Node>;
+}
+
+impl dyn Node {
+    /// Returns true if the node is of type `U`, like `Element<Rect>`
+    pub fn is<U: Node>(&self) -> bool {
+        self.as_any().is::<U>()
+    }
+
+    pub fn downcast_ref<U: Node>(&self) -> Option<&U> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<U: Node>(&mut self) -> Option<&mut U> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+/// Traversal of every element of a tree, unlike [`Visit`](crate::visit::Visit) that renders
+/// the tree. The elements are visited depth first with their depth, the root has a depth of 0
+/// and the definitions of an svg comes before its children. Character data is skipped.
+/// ```
+/// # use svg_maker::{Parent, color::Color, element::Element, node::Walk};
+/// let mut svg = Element::svg()
+///     .push(Element::group().id("g").push(Element::rect(0, 0, 1, 1).fill(Color::Red)));
+/// svg.walk_mut(|node, _| *node.id_mut() = None);
+///
+/// let mut tags = vec![];
+/// svg.walk(|node, depth| tags.push((node.tag().to_string(), depth, node.get_id().is_some())));
+/// assert_eq!(tags[2], ("rect".to_string(), 2, false));
+/// ```
+pub trait Walk {
+    fn walk(&self, f: impl FnMut(&dyn Node, usize));
+    /// Like [`Walk::walk`], the children are visited after the element is changed by `f`.
+    fn walk_mut(&mut self, f: impl FnMut(&mut dyn Node, usize));
+}
+
+impl<N: Node> Walk for N {
+    fn walk(&self, mut f: impl FnMut(&dyn Node, usize)) {
+        walk(self, 0, &mut f);
+    }
+
+    fn walk_mut(&mut self, mut f: impl FnMut(&mut dyn Node, usize)) {
+        walk_mut(self, 0, &mut f);
+    }
+}
+
+impl Walk for dyn Node {
+    fn walk(&self, mut f: impl FnMut(&dyn Node, usize)) {
+        walk(self, 0, &mut f);
+    }
+
+    fn walk_mut(&mut self, mut f: impl FnMut(&mut dyn Node, usize)) {
+        walk_mut(self, 0, &mut f);
+    }
+}
+
+fn walk(node: &dyn Node, depth: usize, f: &mut dyn FnMut(&dyn Node, usize)) {
+    f(node, depth);
+    for child in node.child_nodes() {
+        walk(child, depth + 1, f);
+    }
+}
+
+fn walk_mut(node: &mut dyn Node, depth: usize, f: &mut dyn FnMut(&mut dyn Node, usize)) {
+    f(node, depth);
+    for child in node.child_nodes_mut() {
+        walk_mut(child, depth + 1, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parent, color::Color, element::Element};
+
+    use super::*;
+
+    #[test]
+    fn walk() {
+        let mut svg = Element::svg()
+            .def(Element::circle(0, 0, 1).id("dot").fill(Color::White))
+            .push(
+                Element::group()
+                    .transform(Transform::Translate(1., 2.))
+                    .push(Element::rect(0, 0, 1, 1).fill(Color::Red))
+                    .push(Element::text(0, 0).push("label").fill(Color::Black)),
+            );
+
+        let mut visited = vec![];
+        svg.walk(|node, depth| {
+            visited.push((node.tag().to_string(), depth, node.get_transforms().len()))
+        });
+        let expected = [
+            ("svg", 0, 0),
+            ("circle", 1, 0),
+            ("g", 1, 1),
+            ("rect", 2, 0),
+            ("text", 2, 0),
+        ];
+        assert_eq!(visited, expected.map(|(tag, d, t)| (tag.to_string(), d, t)));
+
+        // fills to css variables
+        svg.walk_mut(|node, _| {
+            let style = node.style_mut();
+            if let Some(Color::Red) = style.fill {
+                style.fill = Some(Color::CssVar("accent".to_string()));
+            }
+        });
+        let mut fills = 0;
+        svg.walk(|node, _| fills += node.get_style().fill.is_some() as usize);
+        assert_eq!(fills, 3);
+        assert!(svg.render(None).contains("fill: var(--accent)"));
+    }
+}
@@ -0,0 +1,855 @@
+// ===== Parsing ================================================================
+//
+// Reads an svg document into an element tree. Elements the crate models are parsed into their
+// typed kind, everything else becomes a `Generic` element with the attributes kept as is.
+// A known element falls back to `Generic` as well if an attribute can't be represented by the
+// typed kind, ie `<rect width="10em">` or path data that can't be parsed, so that nothing is
+// lost.
+//
+// Presentation attributes (fill="red"..) are kept as is, the declarations of the style
+// attribute are moved to the style of the element.
+// Comments and processing instructions are dropped.
+
+use std::{fmt::Display, str::FromStr};
+
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::{
+    Raw,
+    element::{Element, Transform},
+    marker_traits::{ChildOf, ElementKind},
+    path_parser,
+    shapes::{
+        circle::Circle, ellipse::Ellipse, foreign_object::ForeignObject, generic::Generic,
+        group::Group, line::Line, path::Path, polygon::Polygon, polyline::Polyline, rect::Rect,
+        svg::Svg, text::LengthAdjust, text::Text, tspan::Tspan, use_href::Use,
+    },
+    units::{AlignAspectRatio, Coord, Length, MeetOrSlice, Percent, Px, TextAnchor},
+};
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Parses an svg document.
+/// ```
+/// # use svg_maker::parse::parse;
+/// let svg = parse(r#"<svg xmlns="http://www.w3.org/2000/svg"><rect width="10" height="5"/></svg>"#)
+///     .unwrap();
+/// assert_eq!(svg.len(), 1);
+///
+/// let err = parse("<svg>\n<rect></svg>").unwrap_err();
+/// assert_eq!((err.line, err.column), (2, 7));
+/// ```
+pub fn parse(svg: &str) -> Result<Element<Svg>, ParseError> {
+    let opts = ParsingOptions {
+        // exported files often has a doctype
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc = Document::parse_with_options(svg, opts).map_err(|e| {
+        let pos = e.pos();
+        ParseError {
+            line: pos.row,
+            column: pos.col,
+            // the position is already part of the error
+            message: e.to_string().replace(&format!(" at {pos}"), ""),
+        }
+    })?;
+
+    let parser = Parser { doc: &doc };
+    let root = doc.root_element();
+    if !is_svg(root) || root.tag_name().name() != "svg" {
+        return Err(parser.error(
+            root,
+            format!("expected <svg> as root element, found <{}>", tag(root)),
+        ));
+    }
+    let mut attrs = parser.attributes(root);
+    let common = Common::take(&mut attrs);
+    let svg = svg_kind(&mut attrs);
+    let svg = parser.svg_children(root, svg)?;
+    Ok(common.apply(svg, attrs))
+}
+
+impl FromStr for Element<Svg> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// starts at 1
+    pub line: u32,
+    /// starts at 1
+    pub column: u32,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}:{}", self.message, self.line, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A parsed node, before it is added to its parent
+enum Parsed {
+    Svg(Element<Svg>),
+    Group(Element<Group>),
+    Use(Element<Use>),
+    Circle(Element<Circle>),
+    Ellipse(Element<Ellipse>),
+    Line(Element<Line>),
+    Path(Element<Path>),
+    Polygon(Element<Polygon>),
+    Polyline(Element<Polyline>),
+    Rect(Element<Rect>),
+    ForeignObject(Element<ForeignObject>),
+    Text(Element<Text>),
+    Tspan(Element<Tspan>),
+    Generic(Element<Generic>),
+    CharacterData(String),
+}
+
+impl Parsed {
+    fn with_common(self, common: Common, attrs: Attrs) -> Self {
+        match self {
+            Parsed::Svg(e) => Parsed::Svg(common.apply(e, attrs)),
+            Parsed::Group(e) => Parsed::Group(common.apply(e, attrs)),
+            Parsed::Use(e) => Parsed::Use(common.apply(e, attrs)),
+            Parsed::Circle(e) => Parsed::Circle(common.apply(e, attrs)),
+            Parsed::Ellipse(e) => Parsed::Ellipse(common.apply(e, attrs)),
+            Parsed::Line(e) => Parsed::Line(common.apply(e, attrs)),
+            Parsed::Path(e) => Parsed::Path(common.apply(e, attrs)),
+            Parsed::Polygon(e) => Parsed::Polygon(common.apply(e, attrs)),
+            Parsed::Polyline(e) => Parsed::Polyline(common.apply(e, attrs)),
+            Parsed::Rect(e) => Parsed::Rect(common.apply(e, attrs)),
+            Parsed::ForeignObject(e) => Parsed::ForeignObject(common.apply(e, attrs)),
+            Parsed::Text(e) => Parsed::Text(common.apply(e, attrs)),
+            Parsed::Tspan(e) => Parsed::Tspan(common.apply(e, attrs)),
+            Parsed::Generic(e) => Parsed::Generic(common.apply(e, attrs)),
+            Parsed::CharacterData(s) => Parsed::CharacterData(s),
+        }
+    }
+}
+
+/// Parent element kinds and the parsed nodes they can hold, see the content model in lib.rs.
+trait Adopt: ElementKind + Sized {
+    fn adopt(parsed: Parsed) -> Option<Box<dyn ChildOf<Self>>>;
+
+    /// which elements are parsed into a typed kind
+    fn content() -> Content {
+        let graphics = Self::adopt(Parsed::Group(Element::group())).is_some();
+        let text = Self::adopt(Parsed::CharacterData(String::new())).is_some();
+        match (graphics, text) {
+            (true, true) => Content::Any,
+            (false, true) => Content::Text,
+            _ => Content::Graphics,
+        }
+    }
+}
+
+macro_rules! impl_adopt {
+    ($($parent:ident: [$($child:ident),* $(; $data:ident)?]),+ $(,)?) => {
+        $(
+        impl Adopt for $parent {
+            fn adopt(parsed: Parsed) -> Option<Box<dyn ChildOf<Self>>> {
+                #[allow(unreachable_patterns)]
+                match parsed {
+                    $( Parsed::$child(e) => Some(Box::new(e)), )*
+                    $( Parsed::$data(s) => Some(Box::new(s)), )?
+                    _ => None,
+                }
+            }
+        }
+        )+
+    };
+}
+
+crate::content_model!(impl_adopt);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Content {
+    /// graphics and container elements, character data is dropped
+    Graphics,
+    /// tspans and character data
+    Text,
+    /// everything, the content model of a generic element isn't known
+    Any,
+}
+
+struct Parser<'a, 'input> {
+    doc: &'a Document<'input>,
+}
+
+impl Parser<'_, '_> {
+    fn error(&self, node: Node, message: String) -> ParseError {
+        self.error_at(node.range().start, message)
+    }
+
+    fn error_at(&self, pos: usize, message: String) -> ParseError {
+        let pos = self.doc.text_pos_at(pos);
+        ParseError {
+            line: pos.row,
+            column: pos.col,
+            message,
+        }
+    }
+
+    fn children<T: Adopt>(
+        &self,
+        node: Node,
+        mut element: Element<T>,
+    ) -> Result<Element<T>, ParseError> {
+        let content = T::content();
+        for child in node.children() {
+            let parsed = if child.is_element() {
+                self.element(child, content)?
+            } else if let Some(text) = child.text() {
+                match content {
+                    Content::Text => Parsed::CharacterData(text.to_string()),
+                    // whitespace between the elements is indentation
+                    Content::Any if !text.trim().is_empty() => {
+                        Parsed::CharacterData(text.to_string())
+                    }
+                    _ => continue,
+                }
+            } else {
+                continue;
+            };
+            if let Some(child) = T::adopt(parsed) {
+                element = element.push_boxed(child);
+            }
+        }
+        Ok(element)
+    }
+
+    /// The <style> and <defs> of an svg are moved to the css and defs of the svg.
+    fn svg_children(&self, node: Node, mut svg: Element<Svg>) -> Result<Element<Svg>, ParseError> {
+        let mut rest = vec![];
+        for child in node.children().filter(|c| c.is_element()) {
+            match (is_svg(child), child.tag_name().name()) {
+                (true, "style") => {
+                    let css = child
+                        .children()
+                        .filter_map(|c| c.text())
+                        .collect::<String>();
+                    match &mut svg.kind.css {
+                        Some(existing) => {
+                            existing.push('\n');
+                            existing.push_str(&css);
+                        }
+                        None => svg.kind.css = Some(css),
+                    }
+                }
+                (true, "defs") => {
+                    for def in child.children().filter(|c| c.is_element()) {
+                        if let Some(def) = Svg::adopt(self.element(def, Content::Graphics)?) {
+                            svg.kind.defs.push(def);
+                        }
+                    }
+                }
+                _ => rest.push(child),
+            }
+        }
+        for child in rest {
+            if let Some(child) = Svg::adopt(self.element(child, Content::Graphics)?) {
+                svg = svg.push_boxed(child);
+            }
+        }
+        Ok(svg)
+    }
+
+    fn element(&self, node: Node, content: Content) -> Result<Parsed, ParseError> {
+        let mut attrs = self.attributes(node);
+        let common = Common::take(&mut attrs);
+
+        let graphics = matches!(content, Content::Graphics | Content::Any);
+        let text = matches!(content, Content::Text | Content::Any);
+        let name = if is_svg(node) {
+            node.tag_name().name()
+        } else {
+            ""
+        };
+
+        // the attributes are only consumed if the element can be typed
+        let mut kind_attrs = attrs.clone();
+        let a = &mut kind_attrs;
+        let parsed = match name {
+            "svg" if graphics => Some(Parsed::Svg(self.svg_children(node, svg_kind(a))?)),
+            "g" if graphics => Some(Parsed::Group(self.children(node, Element::group())?)),
+            "use" if graphics => use_kind(a).map(Parsed::Use),
+            "circle" if graphics => circle_kind(a).map(Parsed::Circle),
+            "ellipse" if graphics => ellipse_kind(a).map(Parsed::Ellipse),
+            "line" if graphics => line_kind(a).map(Parsed::Line),
+            "path" if graphics => path_kind(a).map(Parsed::Path),
+            "polygon" if graphics => {
+                points(a).map(|p| Parsed::Polygon(Element::polygon().add_points(p)))
+            }
+            "polyline" if graphics => {
+                points(a).map(|p| Parsed::Polyline(Element::polyline().add_points(p)))
+            }
+            "rect" if graphics => rect_kind(a).map(Parsed::Rect),
+            "foreignObject" if graphics => {
+                foreign_object_kind(a).map(|e| Parsed::ForeignObject(self.raw_children(node, e)))
+            }
+            "text" if graphics => text_kind(a)
+                .map(|e| self.children(node, e))
+                .transpose()?
+                .map(Parsed::Text),
+            "tspan" if text => tspan_kind(a)
+                .map(|e| self.children(node, e))
+                .transpose()?
+                .map(Parsed::Tspan),
+            _ => None,
+        };
+
+        match parsed {
+            Some(parsed) => Ok(parsed.with_common(common, kind_attrs)),
+            None => {
+                let generic = Element::new(Generic::new(&tag(node), attrs.list));
+                let generic = self.children(node, generic)?;
+                Ok(Parsed::Generic(common.apply(generic, Attrs::default())))
+            }
+        }
+    }
+
+    /// The content of a foreign object is another language (usually html) and is kept as is.
+    fn raw_children(
+        &self,
+        node: Node,
+        mut element: Element<ForeignObject>,
+    ) -> Element<ForeignObject> {
+        let input = self.doc.input_text();
+        for child in node.children() {
+            if child.is_element() || child.is_text() {
+                element = element.push_boxed(Box::new(Raw::new(&input[child.range()])));
+            }
+        }
+        element
+    }
+
+    /// Returns the attributes and the namespaces declared on the element with qualified names.
+    fn attributes(&self, node: Node) -> Attrs {
+        let mut list = vec![];
+        let inherited = node
+            .parent_element()
+            .map(|p| p.namespaces().collect::<Vec<_>>())
+            .unwrap_or_default();
+        for ns in node.namespaces().filter(|ns| !inherited.contains(ns)) {
+            match ns.name() {
+                Some("xml") => {}
+                Some(prefix) => list.push((format!("xmlns:{prefix}"), ns.uri().to_string())),
+                // the svg kind always renders the svg namespace
+                None if ns.uri() == SVG_NS => {}
+                None => list.push(("xmlns".to_string(), ns.uri().to_string())),
+            }
+        }
+        for attr in node.attributes() {
+            let name = match attr.namespace() {
+                None => attr.name().to_string(),
+                Some(XML_NS) => format!("xml:{}", attr.name()),
+                Some(ns) => match node.lookup_prefix(ns) {
+                    Some(prefix) => format!("{prefix}:{}", attr.name()),
+                    None => attr.name().to_string(),
+                },
+            };
+            list.push((name, attr.value().to_string()));
+        }
+        Attrs { list }
+    }
+}
+
+fn is_svg(node: Node) -> bool {
+    matches!(node.tag_name().namespace(), None | Some(SVG_NS))
+}
+
+/// the qualified tag name of the element
+fn tag(node: Node) -> String {
+    let name = node.tag_name();
+    match name.namespace().and_then(|ns| node.lookup_prefix(ns)) {
+        Some(prefix) if !is_svg(node) => format!("{prefix}:{}", name.name()),
+        _ => name.name().to_string(),
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Attrs {
+    list: Vec<(String, String)>,
+}
+
+impl Attrs {
+    fn take(&mut self, name: &str) -> Option<String> {
+        let i = self.list.iter().position(|(n, _)| n == name)?;
+        Some(self.list.remove(i).1)
+    }
+
+    /// Takes and parses the attribute, an attribute that is missing is `Some(None)` and one that
+    /// can't be parsed is `None`.
+    fn parse<T>(&mut self, name: &str, f: impl Fn(&str) -> Option<T>) -> Option<Option<T>> {
+        match self.take(name) {
+            Some(value) => f(&value).map(Some),
+            None => Some(None),
+        }
+    }
+
+    /// Same as [`Attrs::parse`], but a missing attribute is the default value.
+    fn parse_or_default<T: Default>(
+        &mut self,
+        name: &str,
+        f: impl Fn(&str) -> Option<T>,
+    ) -> Option<T> {
+        self.parse(name, f).map(Option::unwrap_or_default)
+    }
+}
+
+/// The attributes every element has
+struct Common {
+    id: Option<String>,
+    class: Option<String>,
+    style: Option<String>,
+    transform: Option<String>,
+}
+
+impl Common {
+    fn take(attrs: &mut Attrs) -> Self {
+        Self {
+            id: attrs.take("id"),
+            class: attrs.take("class"),
+            style: attrs.take("style"),
+            transform: attrs.take("transform"),
+        }
+    }
+
+    /// Sets the common attributes and the attributes that are left on the element
+    fn apply<T>(self, mut element: Element<T>, mut attrs: Attrs) -> Element<T> {
+        element.id = self.id;
+        element.class = self.class;
+        if let Some(transform) = self.transform {
+            match transforms(&transform) {
+                Some(transforms) => element.transforms = Some(transforms),
+                None => attrs.list.push(("transform".to_string(), transform)),
+            }
+        }
+        if let Some(style) = self.style {
+            match declarations(&style) {
+                Some(declarations) => {
+                    for (k, v) in declarations {
+                        element.style.insert_kv(&k, v);
+                    }
+                }
+                None => attrs.list.push(("style".to_string(), style)),
+            }
+        }
+        element.attrs = attrs.list;
+        element
+    }
+}
+
+// ===== Element kinds ==========================================================
+
+fn svg_kind(attrs: &mut Attrs) -> Element<Svg> {
+    let mut svg = Element::svg();
+    // the root can't fall back to a generic element, values that can't be parsed are kept as is
+    svg.kind.viewbox = None;
+    if let Some(value) = attrs.take("viewBox") {
+        match numbers(&value).as_deref() {
+            Some(&[x, y, w, h]) => svg = svg.viewbox(x, y, w, h),
+            _ => attrs.list.push(("viewBox".to_string(), value)),
+        }
+    }
+    let size = |attrs: &mut Attrs, name: &str| match attrs.take(name) {
+        Some(value) => match length(&value) {
+            Some(l) => l,
+            None => {
+                attrs.list.push((name.to_string(), value));
+                Length::default()
+            }
+        },
+        None => Length::default(),
+    };
+    let (w, h) = (size(attrs, "width"), size(attrs, "height"));
+    svg = svg.size(w, h);
+    if let Some(value) = attrs.take("preserveAspectRatio") {
+        match aspect_ratio(&value) {
+            Some((alignment, meet_or_slice)) => {
+                svg = svg.preserv_aspect_ratio(alignment, meet_or_slice)
+            }
+            None => attrs.list.push(("preserveAspectRatio".to_string(), value)),
+        }
+    }
+    if let Some(version) = attrs.take("version") {
+        svg = svg.version(&version);
+    }
+    svg
+}
+
+fn use_kind(attrs: &mut Attrs) -> Option<Element<Use>> {
+    let href = attrs
+        .take("href")
+        .or_else(|| attrs.take("xlink:href"))
+        .filter(|href| href.starts_with('#'))?;
+    let x = attrs.parse_or_default("x", user_unit)?;
+    let y = attrs.parse_or_default("y", user_unit)?;
+    let mut u = Element::use_href(x, y).href(&href);
+    if let Some(width) = attrs.parse("width", length)? {
+        u = u.width(width);
+    }
+    if let Some(height) = attrs.parse("height", length)? {
+        u = u.height(height);
+    }
+    Some(u)
+}
+
+/// Path data that can't be parsed is kept as is on a generic element
+fn path_kind(attrs: &mut Attrs) -> Option<Element<Path>> {
+    let Some(d) = attrs.take("d") else {
+        return Some(Element::path());
+    };
+    let mut commands = path_parser::parse(&d).ok()?;
+    Some(Element::path().push_commands(&mut commands))
+}
+
+fn circle_kind(attrs: &mut Attrs) -> Option<Element<Circle>> {
+    Some(Element::circle(
+        attrs.parse_or_default("cx", length)?,
+        attrs.parse_or_default("cy", length)?,
+        attrs.parse_or_default("r", length)?,
+    ))
+}
+
+fn ellipse_kind(attrs: &mut Attrs) -> Option<Element<Ellipse>> {
+    Some(Element::ellipse(
+        attrs.parse_or_default("cx", length)?,
+        attrs.parse_or_default("cy", length)?,
+        attrs.parse_or_default("rx", length)?,
+        attrs.parse_or_default("ry", length)?,
+    ))
+}
+
+fn line_kind(attrs: &mut Attrs) -> Option<Element<Line>> {
+    Some(Element::line(
+        attrs.parse_or_default("x1", length)?,
+        attrs.parse_or_default("y1", length)?,
+        attrs.parse_or_default("x2", length)?,
+        attrs.parse_or_default("y2", length)?,
+    ))
+}
+
+fn rect_kind(attrs: &mut Attrs) -> Option<Element<Rect>> {
+    let rect = Element::rect(
+        attrs.parse_or_default("x", length)?,
+        attrs.parse_or_default("y", length)?,
+        attrs.parse_or_default("width", length)?,
+        attrs.parse_or_default("height", length)?,
+    );
+    let rect = match (attrs.parse("rx", length)?, attrs.parse("ry", length)?) {
+        (Some(rx), Some(ry)) => rect.corner_radius_xy(rx, ry),
+        // a missing radius is the same as the other one
+        (Some(r), None) | (None, Some(r)) => rect.corner_radius(r),
+        (None, None) => rect,
+    };
+    Some(rect)
+}
+
+fn foreign_object_kind(attrs: &mut Attrs) -> Option<Element<ForeignObject>> {
+    let mut fo = Element::foreign_object(
+        attrs.parse_or_default("x", length)?,
+        attrs.parse_or_default("y", length)?,
+    );
+    if let Some(width) = attrs.parse("width", length)? {
+        fo = fo.width(width);
+    }
+    if let Some(height) = attrs.parse("height", length)? {
+        fo = fo.height(height);
+    }
+    Some(fo)
+}
+
+fn points(attrs: &mut Attrs) -> Option<Vec<Coord>> {
+    let numbers = attrs.parse_or_default("points", numbers)?;
+    if numbers.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        numbers
+            .chunks(2)
+            .map(|p| Coord::from((p[0], p[1])))
+            .collect(),
+    )
+}
+
+/// Reads the positioning attributes shared by <text> and <tspan>
+macro_rules! text_content_kind {
+    ($attrs:expr, $constructor:expr) => {{
+        let attrs: &mut Attrs = $attrs;
+        let x = attrs.parse_or_default("x", lengths)?;
+        let y = attrs.parse_or_default("y", lengths)?;
+        let mut e = $constructor(x, y)
+            .dx(attrs.parse_or_default("dx", lengths)?)
+            .dy(attrs.parse_or_default("dy", lengths)?);
+        if let Some(rotate) = attrs.parse("rotate", numbers)? {
+            e = e.rotate(rotate);
+        }
+        if let Some(adjust) = attrs.parse("lengthAdjust", length_adjust)? {
+            e = e.length_adjust(adjust);
+        }
+        if let Some(text_length) = attrs.parse("textLength", length)? {
+            e = e.text_length(text_length);
+        }
+        if let Some(anchor) = attrs.parse("text-anchor", text_anchor)? {
+            e = e.text_anchor(anchor);
+        }
+        Some(e)
+    }};
+}
+
+fn text_kind(attrs: &mut Attrs) -> Option<Element<Text>> {
+    text_content_kind!(attrs, Element::<Text>::text_positioned)
+}
+
+fn tspan_kind(attrs: &mut Attrs) -> Option<Element<Tspan>> {
+    text_content_kind!(attrs, Element::<Tspan>::tspan_positioned)
+}
+
+// ===== Attribute values =======================================================
+
+fn number(value: &str) -> Option<f64> {
+    value.trim().parse().ok()
+}
+
+/// whitespace and/or comma separated numbers
+pub(crate) fn numbers(value: &str) -> Option<Vec<f64>> {
+    value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(number)
+        .collect()
+}
+
+/// a length without unit or in px
+fn user_unit(value: &str) -> Option<f64> {
+    let value = value.trim();
+    number(value.strip_suffix("px").unwrap_or(value))
+}
+
+fn length(value: &str) -> Option<Length> {
+    let value = value.trim();
+    if let Some(percent) = value.strip_suffix('%') {
+        // only whole percentages can be represented
+        return percent.parse().ok().map(|p| Percent(p).into());
+    }
+    user_unit(value).map(|px| Px(px).into())
+}
+
+fn lengths(value: &str) -> Option<Vec<Length>> {
+    value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(length)
+        .collect()
+}
+
+fn length_adjust(value: &str) -> Option<LengthAdjust> {
+    match value.trim() {
+        "spacing" => Some(LengthAdjust::Spacing),
+        "spacingAndGlyphs" => Some(LengthAdjust::SpacingAndGlyphs),
+        _ => None,
+    }
+}
+
+fn text_anchor(value: &str) -> Option<TextAnchor> {
+    match value.trim() {
+        "start" => Some(TextAnchor::Start),
+        "middle" => Some(TextAnchor::Middle),
+        "end" => Some(TextAnchor::End),
+        _ => None,
+    }
+}
+
+fn aspect_ratio(value: &str) -> Option<(AlignAspectRatio, MeetOrSlice)> {
+    let mut parts = value.split_whitespace();
+    let alignment = match parts.next()? {
+        "xMinYMin" => AlignAspectRatio::XMinYMin,
+        "xMinYMid" => AlignAspectRatio::XMinYMid,
+        "xMinYMax" => AlignAspectRatio::XMinYMax,
+        "xMidYMin" => AlignAspectRatio::XMidYMin,
+        "xMidYMid" => AlignAspectRatio::XMidYMid,
+        "xMidYMax" => AlignAspectRatio::XMidYMax,
+        "xMaxYMin" => AlignAspectRatio::XMaxYMin,
+        "xMaxYMid" => AlignAspectRatio::XMaxYMid,
+        "xMaxYMax" => AlignAspectRatio::XMaxYMax,
+        // "none" isn't rendered by the svg kind
+        _ => return None,
+    };
+    let meet_or_slice = match parts.next() {
+        None | Some("meet") => MeetOrSlice::Meet,
+        Some("slice") => MeetOrSlice::Slice,
+        _ => return None,
+    };
+    Some((alignment, meet_or_slice))
+}
+
+/// Parses a transform list, ie "translate(10 20) rotate(45)"
+pub(crate) fn transforms(value: &str) -> Option<Vec<Transform>> {
+    let mut transforms = vec![];
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        let (name, args) = rest.split_once('(')?;
+        let (args, tail) = args.split_once(')')?;
+        let transform = match (name.trim(), numbers(args)?.as_slice()) {
+            ("translate", &[x]) => Transform::TranslateX(x),
+            ("translate", &[x, y]) => Transform::Translate(x, y),
+            ("scale", &[xy]) => Transform::ScaleXY(xy),
+            ("scale", &[x, y]) => Transform::Scale(x, y),
+            ("rotate", &[a]) => Transform::Rotate(a),
+            ("rotate", &[a, x, y]) => Transform::RotateXY(a, x, y),
+            ("skewX", &[a]) => Transform::SkewX(a),
+            ("skewY", &[a]) => Transform::SkewY(a),
+            ("matrix", &[a, b, c, d, e, f]) => Transform::Matrix(a, b, c, d, e, f),
+            _ => return None,
+        };
+        transforms.push(transform);
+        rest = tail.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Some(transforms)
+}
+
+/// Splits the declarations of a style attribute, `None` if it is something this simple split
+/// can't handle, like a `;` inside an url.
+pub(crate) fn declarations(style: &str) -> Option<Vec<(String, String)>> {
+    style
+        .split(';')
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| {
+            let (k, v) = d.split_once(':')?;
+            let (k, v) = (k.trim(), v.trim());
+            if k.is_empty() || v.is_empty() || v.contains('(') != v.contains(')') {
+                return None;
+            }
+            Some((k.to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Options;
+
+    use super::*;
+
+    fn compact() -> Options {
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        opts
+    }
+
+    #[test]
+    fn typed_elements() {
+        let svg = parse(
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+            <!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 50" width="200">
+                <!-- background -->
+                <rect id="bg" width="100%" height="50" rx="4" fill="#eee"/>
+                <g class="shapes" transform="translate(10 20) scale(2)">
+                    <circle cx="5" cy="5" r="2"/>
+                    <path d="M0,0 L10,10 Z"/>
+                </g>
+                <text x="10" y="40" text-anchor="middle">Total <tspan dy="4">42</tspan></text>
+            </svg>"##,
+        )
+        .unwrap();
+        assert_eq!(svg.len(), 3);
+
+        let expected = r##"<svg width="200" viewBox="0 0 100 50" version="1.1" xmlns="http://www.w3.org/2000/svg"><rect id="bg" x="0" y="0" width="100%" height="50" rx="4" fill="#eee"/><g class="shapes" transform="translate(10 20) scale(2)"><circle cx="5" cy="5" r="2"/><path d="M0,0 L10,10 Z"/></g><text x="10" y="40" text-anchor="middle">Total <tspan dy="4">42</tspan></text></svg>"##;
+        assert_eq!(svg.render(Some(compact())), expected);
+    }
+
+    #[test]
+    fn unknown_elements_and_attributes() {
+        let svg = parse(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" data-chart="bar">
+                <defs>
+                    <linearGradient id="fade"><stop offset="0" stop-color="red"/></linearGradient>
+                </defs>
+                <style>rect { fill: url(#fade); }</style>
+                <use xlink:href="#fade" x="1" y="2"/>
+                <rect width="10em" height="1"/>
+                <a href="https://example.com"><title>Link</title><circle r="1"/></a>
+            </svg>"##,
+        )
+        .unwrap();
+
+        let rendered = svg.render(Some(compact()));
+        assert!(
+            rendered.contains(r#"xmlns:xlink="http://www.w3.org/1999/xlink" data-chart="bar">"#)
+        );
+        assert!(rendered.contains(r##"<style>rect { fill: url(#fade); }</style><defs><linearGradient id="fade"><stop offset="0" stop-color="red"/></linearGradient></defs>"##));
+        assert!(rendered.contains(r##"<use x="1" y="2" href="#fade"/>"##));
+        assert!(rendered.contains(r#"<rect width="10em" height="1"/>"#));
+        assert!(rendered.contains(
+            r#"<a href="https://example.com"><title>Link</title><circle cx="0" cy="0" r="1"/></a>"#
+        ));
+    }
+
+    #[test]
+    fn style_attribute() {
+        let svg = parse(r#"<svg><rect style="fill: red" stroke="blue"/></svg>"#).unwrap();
+        let rendered = svg.render(Some(compact()));
+        assert!(rendered.contains(r#"style="fill:red;" stroke="blue"/>"#));
+
+        let svg =
+            parse(r#"<svg><rect style="fill: url(data:image/png;base64,AA==)"/></svg>"#).unwrap();
+        let rendered = svg.render(Some(compact()));
+        assert!(rendered.contains(r#"style="fill: url(data:image/png;base64,AA==)"/>"#));
+    }
+
+    #[test]
+    fn errors() {
+        let err = parse("<svg>\n  <rect>\n</svg>").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        assert!(err.to_string().ends_with("at 3:1"));
+
+        let err = parse(r#"<html><svg/></html>"#).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+    }
+
+    #[test]
+    fn path_data() {
+        let svg =
+            parse(r#"<svg><path d="M.5.5L-1-2"/><path d="M0,0 L1" fill="red"/></svg>"#).unwrap();
+        let rendered = svg.render(Some(compact()));
+        assert!(
+            rendered.contains(r#"<path d="M0.5,0.5 L-1,-2"/>"#),
+            "{rendered}"
+        );
+        // the invalid path is kept as a generic element
+        assert!(
+            rendered.contains(r#"<path d="M0,0 L1" fill="red"/>"#),
+            "{rendered}"
+        );
+    }
+
+    #[test]
+    fn attribute_values() {
+        assert!(length("12.5%").is_none());
+        assert!(matches!(length("12px"), Some(Length::Px(Px(12.)))));
+        assert_eq!(numbers("1,2 3\n4").unwrap(), vec![1., 2., 3., 4.]);
+        assert!(transforms("translate(1, 2) matrix(1 0 0 1 0 0)").is_some());
+        assert!(transforms("translate(1, 2) perspective(1)").is_none());
+        assert!(declarations("fill: red; ;stroke:blue").is_some());
+        assert!(declarations("fill").is_none());
+    }
+
+    #[test]
+    fn from_str() {
+        let svg: Element<Svg> = r#"<svg><g/></svg>"#.parse().unwrap();
+        assert_eq!(svg.len(), 1);
+    }
+}
@@ -1,7 +1,6 @@
-use core::panic;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
-use crate::shapes::path::Command;
+use crate::{shapes::path::Command, units::ArcArgs};
 
 fn is_separator(byte: u8) -> bool {
     matches!(byte, b',')
@@ -48,7 +47,14 @@ pub fn parse(value: &str) -> Result<Vec<Command>, ParseError> {
                 let mut rest = vec![*x];
                 // Peek to see if the number is longer than one byte
                 while let Some(next_byte) = iter.peek() {
-                    if is_number_value(*next_byte) {
+                    // a sign starts a new number unless it is the sign of an exponent,
+                    // "10-5" is two numbers. A second point does too, ".5.5" is ".5 .5"
+                    let starts_number = match next_byte {
+                        b'+' | b'-' => !matches!(rest.last(), Some(b'e' | b'E')),
+                        b'.' => rest.iter().any(|b| matches!(b, b'.' | b'e' | b'E')),
+                        _ => false,
+                    };
+                    if is_number_value(*next_byte) && !starts_number {
                         rest.push(iter.next().unwrap());
                     } else {
                         break;
@@ -78,65 +84,124 @@ pub fn parse(value: &str) -> Result<Vec<Command>, ParseError> {
         tokens.push(token);
     }
 
+    let mut parser = Parser { tokens, pos: 0 };
     let mut commands = vec![];
-    let mut iter = tokens.iter().peekable();
-    while let Some(token) = iter.next() {
-        let command = match token {
-            PathToken::Command(c) => match c {
-                b'M' => Command::MoveTo(parse_args(&mut iter)?.into()),
-                b'm' => Command::MoveToRelative(parse_args(&mut iter)?.into()),
-                b'L' => Command::Line(parse_args(&mut iter)?.into()),
-                b'l' => Command::LineRelative(parse_args(&mut iter)?.into()),
-                b'H' => Command::HorizontalLine(parse_args::<1>(&mut iter)?[0].into()),
-                b'h' => Command::HorizontalLineRelative(parse_args::<1>(&mut iter)?[0].into()),
-                b'V' => Command::VerticalLine(parse_args::<1>(&mut iter)?[0].into()),
-                b'v' => Command::VerticalLineRelative(parse_args::<1>(&mut iter)?[0].into()),
-                b'C' => Command::CubicBezier(parse_args::<6>(&mut iter)?.into()),
-                b'c' => Command::CubicBezierRelative(parse_args::<6>(&mut iter)?.into()),
-                b'S' => todo!(),
-                b's' => todo!(),
-                b'Q' => todo!(),
-                b'q' => todo!(),
-                b'T' => todo!(),
-                b't' => todo!(),
-                b'A' => todo!(),
-                b'a' => todo!(),
-                b'Z' => todo!(),
-                b'z' => todo!(),
-                t => {
-                    return Err(ParseError::UnexpectedCharacter(format!(
-                        "expected path command, found {}",
-                        *t as char
-                    )));
-                }
-            },
-            _ => panic!("error for token: {token:?}"),
+    while let Some(token) = parser.next() {
+        let PathToken::Command(mut command) = token else {
+            return Err(ParseError::UnexpectedToken(token));
         };
-        commands.push(command);
+        // a command can be followed by multiple sets of arguments, "L10,10 20,20" is the same
+        // as "L10,10 L20,20". The extra pairs of a moveto are implicit lineto commands.
+        loop {
+            commands.push(parser.command(command)?);
+            if !parser.next_is_number() || matches!(command, b'Z' | b'z') {
+                break;
+            }
+            command = match command {
+                b'M' => b'L',
+                b'm' => b'l',
+                c => c,
+            };
+        }
     }
     Ok(commands)
 }
 
-#[allow(clippy::needless_range_loop)]
-fn parse_args<'a, const N: usize>(
-    iter: &mut impl Iterator<Item = &'a PathToken>,
-) -> Result<[f64; N], ParseError> {
-    // TODO: A command can have mutiple pairs of araguments
-    // a L10,10 L20,20 is the same as L10,10,20,20
-    // So... we need to check
-    // 1. if the next token after the parsed set is a number.
-    // 2. does the next set have N numbers
-    // how should this be returned? boolena flag "has_more"?
-    let mut arr = [0.; N];
-    for i in 0..N {
-        arr[i] = match iter.next() {
-            Some(PathToken::Number(nr)) => bytes_to_f64(nr)?,
-            Some(t) => return Err(ParseError::UnexpectedToken(t.clone())),
-            None => return Err(ParseError::NoTokenFound),
+struct Parser {
+    tokens: Vec<PathToken>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<PathToken> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn next_is_number(&self) -> bool {
+        matches!(self.tokens.get(self.pos), Some(PathToken::Number(_)))
+    }
+
+    fn command(&mut self, command: u8) -> Result<Command, ParseError> {
+        let command = match command {
+            b'M' => Command::MoveTo(self.args()?.into()),
+            b'm' => Command::MoveToRelative(self.args()?.into()),
+            b'L' => Command::Line(self.args()?.into()),
+            b'l' => Command::LineRelative(self.args()?.into()),
+            b'H' => Command::HorizontalLine(self.args::<1>()?[0].into()),
+            b'h' => Command::HorizontalLineRelative(self.args::<1>()?[0].into()),
+            b'V' => Command::VerticalLine(self.args::<1>()?[0].into()),
+            b'v' => Command::VerticalLineRelative(self.args::<1>()?[0].into()),
+            b'C' => Command::CubicBezier(self.args::<6>()?.into()),
+            b'c' => Command::CubicBezierRelative(self.args::<6>()?.into()),
+            b'S' => Command::CubicBezierExtended(self.args::<4>()?.into()),
+            b's' => Command::CubicBezierExtendedRelative(self.args::<4>()?.into()),
+            b'Q' => Command::QuadraticBezier(self.args::<4>()?.into()),
+            b'q' => Command::QuadraticBezierRelative(self.args::<4>()?.into()),
+            b'T' => Command::QuadraticBezierExtended(self.args()?.into()),
+            b't' => Command::QuadraticBezierExtendedRelative(self.args()?.into()),
+            b'A' => Command::Arc(self.arc_args()?),
+            b'a' => Command::ArcRelative(self.arc_args()?),
+            b'Z' | b'z' => Command::ClosePath,
+            t => {
+                return Err(ParseError::UnexpectedCharacter(format!(
+                    "expected path command, found {}",
+                    t as char
+                )));
+            }
         };
+        Ok(command)
+    }
+
+    #[allow(clippy::needless_range_loop)]
+    fn args<const N: usize>(&mut self) -> Result<[f64; N], ParseError> {
+        let mut arr = [0.; N];
+        for i in 0..N {
+            arr[i] = match self.next() {
+                Some(PathToken::Number(nr)) => bytes_to_f64(&nr)?,
+                Some(t) => return Err(ParseError::UnexpectedToken(t)),
+                None => return Err(ParseError::NoTokenFound),
+            };
+        }
+        Ok(arr)
     }
 
-    Ok(arr)
+    fn arc_args(&mut self) -> Result<ArcArgs, ParseError> {
+        let [rx, ry, x_axis_rotation] = self.args()?;
+        let large_arc = self.flag()?;
+        let sweep = self.flag()?;
+        Ok(ArcArgs {
+            rx,
+            ry,
+            x_axis_rotation,
+            large_arc,
+            sweep,
+            end: self.args()?.into(),
+        })
+    }
+
+    /// Reads a single 0 or 1. The flags of an arc doesn't need a separator, "a1 1 0 0110,10"
+    /// is valid so the flag is split off the front of the number.
+    fn flag(&mut self) -> Result<bool, ParseError> {
+        let Some(PathToken::Number(nr)) = self.tokens.get_mut(self.pos) else {
+            return match self.next() {
+                Some(t) => Err(ParseError::UnexpectedToken(t)),
+                None => Err(ParseError::NoTokenFound),
+            };
+        };
+        let flag = match nr.first() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(ParseError::InvalidFlag),
+        };
+        if nr.len() > 1 {
+            nr.remove(0);
+        } else {
+            self.pos += 1;
+        }
+        Ok(flag)
+    }
 }
 
 fn bytes_to_f64(bytes: &[u8]) -> Result<f64, ParseError> {
@@ -153,6 +218,20 @@ pub enum ParseError {
     UnexpectedCharacter(String),
     UnexpectedToken(PathToken),
     NoTokenFound,
+    /// the large arc and sweep flags of an arc must be 0 or 1
+    InvalidFlag,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::ParseToF64 => write!(f, "invalid number"),
+            ParseError::UnexpectedCharacter(e) => write!(f, "{e}"),
+            ParseError::UnexpectedToken(t) => write!(f, "unexpected {t:?}"),
+            ParseError::NoTokenFound => write!(f, "missing argument"),
+            ParseError::InvalidFlag => write!(f, "arc flags must be 0 or 1"),
+        }
+    }
 }
 
 #[derive(Clone, PartialEq)]
@@ -204,7 +283,7 @@ mod tests {
     use crate::{
         buffer::Buffer,
         path_parser::is_command,
-        units::{Coord, CubicArgs, XCoord, YCoord},
+        units::{ArcArgs, Coord, CubicArgs, XCoord, YCoord},
         visit::Visit,
     };
 
@@ -548,7 +627,7 @@ mod tests {
 
         #[test]
         fn test_invalid_numbers() {
-            assert!(matches!(parse("M1.2.3,20"), Err(ParseError::ParseToF64)));
+            assert!(matches!(parse("M.,20"), Err(ParseError::ParseToF64)));
             assert!(matches!(
                 parse("Mabc,20"),
                 Err(ParseError::UnexpectedCharacter(_))
@@ -619,6 +698,21 @@ mod tests {
             assert_parse_success(input, expected);
         }
 
+        #[test]
+        fn test_curve_and_arc_commands() {
+            let input = "M0,0 S1,2 3,4 q1,2 3,4 T5,6 A10,10 0 1,0 20,20 z";
+            let commands = parse(input).unwrap();
+            assert_eq!(commands.len(), 6);
+            assert_eq!(commands[5], Command::ClosePath);
+
+            let mut buffer = Buffer::with_capacity(10);
+            commands.visit(&mut buffer);
+            assert_eq!(
+                buffer.str(),
+                "M0,0 S1,2 3,4 q1,2 3,4 T5,6 A10,10 0 1,0 20,20 Z"
+            );
+        }
+
         #[test]
         fn test_all_command_types() {
             let input = "M10,20 L30,40 H50 V60 C1,2,3,4,5,6";
@@ -637,6 +731,76 @@ mod tests {
     mod edge_cases {
         use super::*;
 
+        #[test]
+        fn test_implicit_repeated_commands() {
+            assert_parse_success(
+                "M10,20 30,40 L1,2 3,4",
+                vec![
+                    Command::MoveTo(Coord::from((10.0, 20.0))),
+                    Command::Line(Coord::from((30.0, 40.0))),
+                    Command::Line(Coord::from((1.0, 2.0))),
+                    Command::Line(Coord::from((3.0, 4.0))),
+                ],
+            );
+            assert_parse_success(
+                "m1 2 3 4",
+                vec![
+                    Command::MoveToRelative(Coord::from((1.0, 2.0))),
+                    Command::LineRelative(Coord::from((3.0, 4.0))),
+                ],
+            );
+            assert!(matches!(
+                parse("M1,2 Z 3"),
+                Err(ParseError::UnexpectedToken(_))
+            ));
+        }
+
+        #[test]
+        fn test_sign_separates_numbers() {
+            assert_parse_success(
+                "M10-20l-5-5",
+                vec![
+                    Command::MoveTo(Coord::from((10.0, -20.0))),
+                    Command::LineRelative(Coord::from((-5.0, -5.0))),
+                ],
+            );
+        }
+
+        #[test]
+        fn test_point_separates_numbers() {
+            assert_parse_success(
+                "M.5.5L-1-2",
+                vec![
+                    Command::MoveTo(Coord::from((0.5, 0.5))),
+                    Command::Line(Coord::from((-1.0, -2.0))),
+                ],
+            );
+            assert_parse_success(
+                "m1.5.25 1e2.5",
+                vec![
+                    Command::MoveToRelative(Coord::from((1.5, 0.25))),
+                    Command::LineRelative(Coord::from((100.0, 0.5))),
+                ],
+            );
+        }
+
+        #[test]
+        fn test_compact_arc_flags() {
+            let arc = ArcArgs {
+                rx: 1.,
+                ry: 2.,
+                x_axis_rotation: 30.,
+                large_arc: false,
+                sweep: true,
+                end: Coord::from((10.0, 10.0)),
+            };
+            assert_parse_success("a1 2 30 0110 10", vec![Command::ArcRelative(arc)]);
+            assert!(matches!(
+                parse("A1 2 30 2 1 10 10"),
+                Err(ParseError::InvalidFlag)
+            ));
+        }
+
         #[test]
         fn test_very_large_numbers() {
             assert_parse_success(
@@ -0,0 +1,1524 @@
+// ===== Rasterizer =============================================================
+//
+// Renders svgs to pixels without a browser, for thumbnails and visual regression tests. The
+// element is rendered and read back as xml, so every kind and `Generic` elements like gradients
+// are handled the same way. The painting itself is done by tiny-skia.
+//
+// Supported: shapes, paths, groups, nested svgs, `<use>`, transforms, fills (nonzero/evenodd),
+// strokes (width, caps, joins, miter limit, dashes), opacity and linear/radial gradients.
+// Presentation attributes and the style attribute are applied, the css of the svg and classes
+// are not. Text, foreign objects, clip paths, masks, patterns, markers and filters are skipped.
+// A path with invalid data is a `RasterError::Path` instead of a partial image.
+
+use std::{collections::HashMap, f64::consts::FRAC_PI_2, fmt::Display, fs, io, path::Path};
+
+use roxmltree::{Document, Node, NodeId};
+use tiny_skia::{
+    FillRule, GradientStop, LineCap, LineJoin, LinearGradient, NonZeroRect, Paint, PathBuilder,
+    Pixmap, PixmapPaint, Point, RadialGradient, Rect, Shader, SpreadMode, Stroke, StrokeDash,
+    Transform,
+};
+
+use crate::{
+    Options,
+    color::named_color,
+    element,
+    element::Element,
+    parse,
+    path_parser::{self, ParseError},
+    shapes::path::Command,
+    shapes::svg::Svg,
+    units::{ArcArgs, Coord},
+};
+
+/// Size of an svg without a width, height or viewBox, the same as in browsers
+const DEFAULT_SIZE: (f32, f32) = (300., 150.);
+/// Limit of nested `<use>` elements, a reference cycle renders nothing
+const MAX_USE_DEPTH: usize = 32;
+
+#[derive(Debug)]
+pub enum RasterError {
+    Xml(roxmltree::Error),
+    /// The width or height of the image is zero or too large
+    Size(u32, u32),
+    Png(String),
+    Io(io::Error),
+    /// The `d` of a path can't be parsed, the data and the error of the first invalid path
+    Path(String, String),
+}
+
+impl Display for RasterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RasterError::Xml(err) => write!(f, "invalid svg: {err}"),
+            RasterError::Size(w, h) => write!(f, "can't create an image of {w}x{h} pixels"),
+            RasterError::Png(err) => write!(f, "png: {err}"),
+            RasterError::Io(err) => write!(f, "{err}"),
+            RasterError::Path(d, err) => write!(f, "invalid path data \"{d}\": {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RasterError {}
+
+impl Element<Svg> {
+    /// Renders the svg to an image, the svg is scaled to fit `size` if it is set, otherwise the
+    /// image has the width and height of the svg.
+    /// ```
+    /// # use svg_maker::{Parent, color::Color, element::Element};
+    /// let svg = Element::svg()
+    ///     .size(20, 10)
+    ///     .viewbox(0, 0, 20, 10)
+    ///     .push(Element::rect(0, 0, 10, 10).fill(Color::Red));
+    /// let image = svg.rasterize(None).unwrap();
+    /// assert_eq!((image.width(), image.height()), (20, 10));
+    /// assert_eq!(image.pixel(5, 5), Some([255, 0, 0, 255]));
+    /// assert_eq!(image.pixel(15, 5), Some([0, 0, 0, 0]));
+    ///
+    /// let thumbnail = svg.rasterize(Some((4, 2))).unwrap();
+    /// assert_eq!(thumbnail.pixel(0, 0), Some([255, 0, 0, 255]));
+    /// ```
+    pub fn rasterize(&self, size: Option<(u32, u32)>) -> Result<Image, RasterError> {
+        let mut opts = Options {
+            precision: None,
+            ..Default::default()
+        };
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        rasterize(&self.render(Some(opts)), size)
+    }
+}
+
+/// Renders an svg document to an image, see [`Element::rasterize`].
+pub fn rasterize(svg: &str, size: Option<(u32, u32)>) -> Result<Image, RasterError> {
+    let doc = Document::parse(svg).map_err(RasterError::Xml)?;
+    let root = doc.root_element();
+    let viewbox = viewbox(root);
+    let width = root.attribute("width").and_then(user_unit);
+    let height = root.attribute("height").and_then(user_unit);
+    let (width, height) = match (width, height, viewbox) {
+        (Some(w), Some(h), _) => (w, h),
+        (Some(w), None, Some(vb)) => (w, w * vb.height() / vb.width()),
+        (None, Some(h), Some(vb)) => (h * vb.width() / vb.height(), h),
+        (None, None, Some(vb)) => (vb.width(), vb.height()),
+        (w, h, None) => (w.unwrap_or(DEFAULT_SIZE.0), h.unwrap_or(DEFAULT_SIZE.1)),
+    };
+    let (pixels_w, pixels_h) = size.unwrap_or((width.ceil() as u32, height.ceil() as u32));
+    let mut pixmap =
+        Pixmap::new(pixels_w, pixels_h).ok_or(RasterError::Size(pixels_w, pixels_h))?;
+
+    // the viewport of the svg is centered in the image
+    let fit = Viewport::new(0., 0., width, height)
+        .map(|vp| vp.transform(pixels_w as f32, pixels_h as f32, Align::default()))
+        .unwrap_or_default();
+
+    let mut ctx = Context {
+        ids: doc
+            .descendants()
+            .filter_map(|n| Some((n.attribute("id")?, n)))
+            .collect(),
+        viewport: (width, height),
+        uses: vec![],
+        error: None,
+    };
+    let mut props = Props::default();
+    props.apply(&declarations(root));
+    ctx.render_svg(root, &props, fit, (width, height), &mut pixmap);
+    match ctx.error {
+        Some(err) => Err(err),
+        None => Ok(Image { pixmap }),
+    }
+}
+
+/// An image rendered by [`Element::rasterize`] or read from a png.
+#[derive(Clone, PartialEq)]
+pub struct Image {
+    /// premultiplied rgba
+    pixmap: Pixmap,
+}
+
+impl std::fmt::Debug for Image {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Image({}x{})", self.width(), self.height())
+    }
+}
+
+impl Image {
+    pub fn width(&self) -> u32 {
+        self.pixmap.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.pixmap.height()
+    }
+
+    /// The rgba color of a pixel, `None` outside of the image
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        let c = self.pixmap.pixel(x, y)?.demultiply();
+        Some([c.red(), c.green(), c.blue(), c.alpha()])
+    }
+
+    /// The pixels row by row, 4 bytes of rgba per pixel
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect()
+    }
+
+    pub fn encode_png(&self) -> Result<Vec<u8>, RasterError> {
+        self.pixmap
+            .encode_png()
+            .map_err(|err| RasterError::Png(err.to_string()))
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), RasterError> {
+        fs::write(path, self.encode_png()?).map_err(RasterError::Io)
+    }
+
+    pub fn from_png(data: &[u8]) -> Result<Self, RasterError> {
+        let pixmap = Pixmap::decode_png(data).map_err(|err| RasterError::Png(err.to_string()))?;
+        Ok(Image { pixmap })
+    }
+
+    /// Compares the images pixel by pixel, a pixel differs if a channel differs by more than
+    /// `tolerance`. `None` if the images don't have the same size.
+    /// ```
+    /// # use svg_maker::{Parent, color::Color, element::Element};
+    /// let svg = |color| {
+    ///     let circle = Element::circle(5, 5, 3).fill(color);
+    ///     Element::svg().size(10, 10).viewbox(0, 0, 10, 10).push(circle)
+    /// };
+    /// let old = svg(Color::Red).rasterize(None).unwrap();
+    /// let new = svg(Color::Black).rasterize(None).unwrap();
+    /// assert!(old.diff(&old, 0).unwrap().is_empty());
+    /// let diff = new.diff(&old, 2).unwrap();
+    /// assert_eq!(diff.max_delta, 255);
+    /// assert!(diff.pixels > 20);
+    /// ```
+    pub fn diff(&self, other: &Image, tolerance: u8) -> Option<PixelDiff> {
+        if (self.width(), self.height()) != (other.width(), other.height()) {
+            return None;
+        }
+        let mut highlighted = self.pixmap.clone();
+        let mut pixels = 0;
+        let mut max_delta = 0;
+        for ((a, b), out) in self
+            .pixmap
+            .pixels()
+            .iter()
+            .zip(other.pixmap.pixels())
+            .zip(highlighted.pixels_mut())
+        {
+            let (a, b) = (a.demultiply(), b.demultiply());
+            let delta = [
+                a.red().abs_diff(b.red()),
+                a.green().abs_diff(b.green()),
+                a.blue().abs_diff(b.blue()),
+                a.alpha().abs_diff(b.alpha()),
+            ]
+            .into_iter()
+            .max()
+            .unwrap_or(0);
+            max_delta = max_delta.max(delta);
+            *out = if delta > tolerance {
+                pixels += 1;
+                tiny_skia::PremultipliedColorU8::from_rgba(255, 0, 0, 255).unwrap()
+            } else {
+                // the matching pixels are faded
+                let faded = out.alpha() / 4;
+                tiny_skia::ColorU8::from_rgba(out.red(), out.green(), out.blue(), faded)
+                    .premultiply()
+            };
+        }
+        Some(PixelDiff {
+            pixels,
+            max_delta,
+            image: Image {
+                pixmap: highlighted,
+            },
+        })
+    }
+}
+
+/// The result of [`Image::diff`]
+#[derive(Debug, Clone)]
+pub struct PixelDiff {
+    /// number of pixels that differ more than the tolerance
+    pub pixels: usize,
+    /// the largest difference of a channel
+    pub max_delta: u8,
+    /// the differing pixels in red, the rest faded
+    pub image: Image,
+}
+
+impl PixelDiff {
+    pub fn is_empty(&self) -> bool {
+        self.pixels == 0
+    }
+}
+
+impl Display for PixelDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} pixels differ by up to {}",
+            self.pixels, self.max_delta
+        )
+    }
+}
+
+// ===== Rendering ==============================================================
+
+struct Context<'a, 'input> {
+    ids: HashMap<&'a str, Node<'a, 'input>>,
+    /// size of the nearest svg, percentages are relative to it
+    viewport: (f32, f32),
+    /// the `<use>` elements being rendered
+    uses: Vec<NodeId>,
+    /// the first error while rendering, returned instead of the image
+    error: Option<RasterError>,
+}
+
+impl<'a, 'input> Context<'a, 'input> {
+    fn render(
+        &mut self,
+        node: Node<'a, 'input>,
+        parent: &Props,
+        ts: Transform,
+        pixmap: &mut Pixmap,
+    ) {
+        if !node.is_element() || !is_rendered(node.tag_name().name()) {
+            return;
+        }
+        let declarations = declarations(node);
+        if property(&declarations, "display") == Some("none") {
+            return;
+        }
+        let mut props = parent.clone();
+        props.apply(&declarations);
+
+        let ts = match node.attribute("transform").map(transform) {
+            Some(Some(transform)) => ts.pre_concat(transform),
+            Some(None) => return,
+            None => ts,
+        };
+
+        let opacity = property(&declarations, "opacity")
+            .and_then(fraction)
+            .unwrap_or(1.);
+        if opacity < 1. {
+            // the element is rendered as a whole before it is made transparent
+            let Some(mut layer) = Pixmap::new(pixmap.width(), pixmap.height()) else {
+                return;
+            };
+            self.draw(node, &props, ts, &mut layer);
+            let paint = PixmapPaint {
+                opacity,
+                ..Default::default()
+            };
+            pixmap.draw_pixmap(0, 0, layer.as_ref(), &paint, Transform::identity(), None);
+        } else {
+            self.draw(node, &props, ts, pixmap);
+        }
+    }
+
+    fn draw(&mut self, node: Node<'a, 'input>, props: &Props, ts: Transform, pixmap: &mut Pixmap) {
+        match node.tag_name().name() {
+            "svg" => {
+                let x = self.x(node, "x", 0.);
+                let y = self.y(node, "y", 0.);
+                let viewport = (
+                    self.x(node, "width", self.viewport.0),
+                    self.y(node, "height", self.viewport.1),
+                );
+                self.render_svg(node, props, ts.pre_translate(x, y), viewport, pixmap);
+            }
+            "g" | "a" => self.render_children(node, props, ts, pixmap),
+            "use" => {
+                let Some(target) = href(node).and_then(|id| self.ids.get(id).copied()) else {
+                    return;
+                };
+                if self.uses.len() >= MAX_USE_DEPTH || self.uses.contains(&node.id()) {
+                    return;
+                }
+                let ts = ts.pre_translate(self.x(node, "x", 0.), self.y(node, "y", 0.));
+                self.uses.push(node.id());
+                self.render(target, props, ts, pixmap);
+                self.uses.pop();
+            }
+            _ => {
+                if let Some(path) = self.shape(node) {
+                    self.fill_and_stroke(&path, props, ts, pixmap);
+                }
+            }
+        }
+    }
+
+    fn render_children(
+        &mut self,
+        node: Node<'a, 'input>,
+        props: &Props,
+        ts: Transform,
+        pixmap: &mut Pixmap,
+    ) {
+        for child in node.children() {
+            self.render(child, props, ts, pixmap);
+        }
+    }
+
+    /// Renders the children of an svg with the viewBox mapped to its viewport
+    fn render_svg(
+        &mut self,
+        node: Node<'a, 'input>,
+        props: &Props,
+        ts: Transform,
+        (width, height): (f32, f32),
+        pixmap: &mut Pixmap,
+    ) {
+        let (ts, viewport) = match viewbox(node) {
+            Some(vb) => {
+                let align = node
+                    .attribute("preserveAspectRatio")
+                    .map(Align::parse)
+                    .unwrap_or_default();
+                (
+                    ts.pre_concat(vb.transform(width, height, align)),
+                    (vb.width(), vb.height()),
+                )
+            }
+            None => (ts, (width, height)),
+        };
+        let outer = std::mem::replace(&mut self.viewport, viewport);
+        self.render_children(node, props, ts, pixmap);
+        self.viewport = outer;
+    }
+
+    fn shape(&mut self, node: Node) -> Option<tiny_skia::Path> {
+        match node.tag_name().name() {
+            "rect" => {
+                let (x, y) = (self.x(node, "x", 0.), self.y(node, "y", 0.));
+                let (w, h) = (self.x(node, "width", 0.), self.y(node, "height", 0.));
+                let rx = node
+                    .attribute("rx")
+                    .and_then(|v| length(v, self.viewport.0));
+                let ry = node
+                    .attribute("ry")
+                    .and_then(|v| length(v, self.viewport.1));
+                let (rx, ry) = match (rx, ry) {
+                    (Some(rx), Some(ry)) => (rx, ry),
+                    (Some(r), None) | (None, Some(r)) => (r, r),
+                    (None, None) => (0., 0.),
+                };
+                rect(x, y, w, h, rx.clamp(0., w / 2.), ry.clamp(0., h / 2.))
+            }
+            "circle" => {
+                let r = self.other(node, "r");
+                PathBuilder::from_circle(self.x(node, "cx", 0.), self.y(node, "cy", 0.), r)
+            }
+            "ellipse" => {
+                let (cx, cy) = (self.x(node, "cx", 0.), self.y(node, "cy", 0.));
+                let (rx, ry) = (self.x(node, "rx", 0.), self.y(node, "ry", 0.));
+                PathBuilder::from_oval(Rect::from_xywh(cx - rx, cy - ry, rx * 2., ry * 2.)?)
+            }
+            "line" => {
+                let mut builder = PathBuilder::new();
+                builder.move_to(self.x(node, "x1", 0.), self.y(node, "y1", 0.));
+                builder.line_to(self.x(node, "x2", 0.), self.y(node, "y2", 0.));
+                builder.finish()
+            }
+            tag @ ("polyline" | "polygon") => {
+                let points = parse::numbers(node.attribute("points")?)?;
+                let mut builder = PathBuilder::new();
+                for (i, point) in points.chunks_exact(2).enumerate() {
+                    let (x, y) = (point[0] as f32, point[1] as f32);
+                    if i == 0 {
+                        builder.move_to(x, y);
+                    } else {
+                        builder.line_to(x, y);
+                    }
+                }
+                if tag == "polygon" {
+                    builder.close();
+                }
+                builder.finish()
+            }
+            "path" => {
+                let d = node.attribute("d")?;
+                let mut builder = PathBuilder::new();
+                let parsed = path_parser::parse(d)
+                    .and_then(|commands| Cursor::default().commands(&commands, &mut builder));
+                if let Err(err) = parsed {
+                    self.error
+                        .get_or_insert(RasterError::Path(d.to_string(), err.to_string()));
+                    return None;
+                }
+                builder.finish()
+            }
+            _ => None,
+        }
+    }
+
+    fn fill_and_stroke(
+        &self,
+        path: &tiny_skia::Path,
+        props: &Props,
+        ts: Transform,
+        pixmap: &mut Pixmap,
+    ) {
+        if let Some(paint) = self.paint(&props.fill, props.fill_opacity, path) {
+            pixmap.fill_path(path, &paint, props.fill_rule, ts, None);
+        }
+        if props.stroke_width <= 0. {
+            return;
+        }
+        if let Some(paint) = self.paint(&props.stroke, props.stroke_opacity, path) {
+            let dash = props.dasharray.as_ref().and_then(|array| {
+                // an odd number of dashes is repeated
+                let array = match array.len() % 2 {
+                    0 => array.clone(),
+                    _ => array.repeat(2),
+                };
+                StrokeDash::new(array, props.dashoffset)
+            });
+            let stroke = Stroke {
+                width: props.stroke_width,
+                miter_limit: props.miter_limit,
+                line_cap: props.line_cap,
+                line_join: props.line_join,
+                dash,
+            };
+            pixmap.stroke_path(path, &paint, &stroke, ts, None);
+        }
+    }
+
+    fn paint(
+        &self,
+        value: &PaintValue,
+        opacity: f32,
+        path: &tiny_skia::Path,
+    ) -> Option<Paint<'static>> {
+        let shader = match value {
+            PaintValue::None => return None,
+            PaintValue::Color(color) => {
+                let mut color = *color;
+                color.apply_opacity(opacity);
+                Shader::SolidColor(color)
+            }
+            PaintValue::Url(id, fallback) => match self.ids.get(id.as_str()) {
+                Some(gradient) => self.gradient(*gradient, opacity, path)?,
+                None => return self.paint(fallback, opacity, path),
+            },
+        };
+        Some(Paint {
+            shader,
+            anti_alias: true,
+            ..Default::default()
+        })
+    }
+
+    fn gradient(
+        &self,
+        node: Node,
+        opacity: f32,
+        path: &tiny_skia::Path,
+    ) -> Option<Shader<'static>> {
+        let tag = node.tag_name().name();
+        if !matches!(tag, "linearGradient" | "radialGradient") {
+            return None;
+        }
+        let attr = |name| self.gradient_attr(node, name);
+        let bbox_units = attr("gradientUnits") != Some("userSpaceOnUse");
+        let (mut ts, (vw, vh)) = if bbox_units {
+            (
+                Transform::from_bbox(path.bounds().to_non_zero_rect()?),
+                (1., 1.),
+            )
+        } else {
+            (Transform::identity(), self.viewport)
+        };
+        if let Some(gradient_ts) = attr("gradientTransform") {
+            ts = ts.pre_concat(transform(gradient_ts)?);
+        }
+        let diagonal = ((vw * vw + vh * vh) / 2.).sqrt();
+        let value = |name, default: &str, reference| {
+            length(attr(name).unwrap_or(default), reference).unwrap_or(0.)
+        };
+        let spread = match attr("spreadMethod") {
+            Some("reflect") => SpreadMode::Reflect,
+            Some("repeat") => SpreadMode::Repeat,
+            _ => SpreadMode::Pad,
+        };
+
+        let stops = self.stops(node, opacity);
+        match stops.as_slice() {
+            [] => return None,
+            [(_, color)] => return Some(Shader::SolidColor(*color)),
+            _ => {}
+        }
+        let stops = stops
+            .into_iter()
+            .map(|(offset, color)| GradientStop::new(offset, color))
+            .collect();
+        if tag == "linearGradient" {
+            let start = Point::from_xy(value("x1", "0%", vw), value("y1", "0%", vh));
+            let end = Point::from_xy(value("x2", "100%", vw), value("y2", "0%", vh));
+            LinearGradient::new(start, end, stops, spread, ts)
+        } else {
+            let (cx, cy) = (value("cx", "50%", vw), value("cy", "50%", vh));
+            let r = value("r", "50%", diagonal);
+            let focus = Point::from_xy(
+                attr("fx").and_then(|v| length(v, vw)).unwrap_or(cx),
+                attr("fy").and_then(|v| length(v, vh)).unwrap_or(cy),
+            );
+            RadialGradient::new(focus, Point::from_xy(cx, cy), r, stops, spread, ts)
+        }
+    }
+
+    /// An attribute of a gradient or of the gradient it references
+    fn gradient_attr(&self, node: Node<'a, 'input>, name: &str) -> Option<&'a str> {
+        let mut node = node;
+        for _ in 0..MAX_USE_DEPTH {
+            if let Some(value) = node.attribute(name) {
+                return Some(value);
+            }
+            node = *self.ids.get(href(node)?)?;
+        }
+        None
+    }
+
+    /// The offsets and colors of the stops of a gradient, the stops of the referenced gradient
+    /// are used if it has none.
+    fn stops(&self, node: Node<'a, 'input>, opacity: f32) -> Vec<(f32, tiny_skia::Color)> {
+        let mut node = node;
+        for _ in 0..MAX_USE_DEPTH {
+            let stops = node
+                .children()
+                .filter(|n| n.tag_name().name() == "stop")
+                .collect::<Vec<_>>();
+            if !stops.is_empty() {
+                let mut last = 0.;
+                return stops
+                    .into_iter()
+                    .map(|stop| {
+                        let declarations = declarations(stop);
+                        // offsets can't decrease
+                        let offset = stop.attribute("offset").and_then(fraction).unwrap_or(0.);
+                        last = offset.max(last);
+                        let mut color = property(&declarations, "stop-color")
+                            .and_then(color)
+                            .unwrap_or(tiny_skia::Color::BLACK);
+                        let stop_opacity = property(&declarations, "stop-opacity")
+                            .and_then(fraction)
+                            .unwrap_or(1.);
+                        color.apply_opacity(stop_opacity * opacity);
+                        (last, color)
+                    })
+                    .collect();
+            }
+            match href(node).and_then(|id| self.ids.get(id)) {
+                Some(referenced) => node = *referenced,
+                None => break,
+            }
+        }
+        vec![]
+    }
+
+    fn x(&self, node: Node, name: &str, default: f32) -> f32 {
+        node.attribute(name)
+            .and_then(|v| length(v, self.viewport.0))
+            .unwrap_or(default)
+    }
+
+    fn y(&self, node: Node, name: &str, default: f32) -> f32 {
+        node.attribute(name)
+            .and_then(|v| length(v, self.viewport.1))
+            .unwrap_or(default)
+    }
+
+    /// A length that isn't horizontal or vertical, like the radius of a circle
+    fn other(&self, node: Node, name: &str) -> f32 {
+        let (w, h) = self.viewport;
+        node.attribute(name)
+            .and_then(|v| length(v, ((w * w + h * h) / 2.).sqrt()))
+            .unwrap_or(0.)
+    }
+}
+
+/// Elements that are rendered where they are in the tree, definitions are only rendered when
+/// they are referenced.
+fn is_rendered(tag: &str) -> bool {
+    matches!(
+        tag,
+        "svg"
+            | "g"
+            | "a"
+            | "use"
+            | "rect"
+            | "circle"
+            | "ellipse"
+            | "line"
+            | "polyline"
+            | "polygon"
+            | "path"
+    )
+}
+
+fn href<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.attribute("href")
+        .or_else(|| node.attribute(("http://www.w3.org/1999/xlink", "href")))?
+        .strip_prefix('#')
+}
+
+fn rect(x: f32, y: f32, w: f32, h: f32, rx: f32, ry: f32) -> Option<tiny_skia::Path> {
+    let rect = Rect::from_xywh(x, y, w, h)?;
+    if rx <= 0. || ry <= 0. {
+        return Some(PathBuilder::from_rect(rect));
+    }
+    // distance of the control points from the corner, for a quarter circle
+    const KAPPA: f32 = 0.552_284_8;
+    let (kx, ky) = (rx * (1. - KAPPA), ry * (1. - KAPPA));
+    let (right, bottom) = (x + w, y + h);
+    let mut b = PathBuilder::new();
+    b.move_to(x + rx, y);
+    b.line_to(right - rx, y);
+    b.cubic_to(right - kx, y, right, y + ky, right, y + ry);
+    b.line_to(right, bottom - ry);
+    b.cubic_to(right, bottom - ky, right - kx, bottom, right - rx, bottom);
+    b.line_to(x + rx, bottom);
+    b.cubic_to(x + kx, bottom, x, bottom - ky, x, bottom - ry);
+    b.line_to(x, y + ry);
+    b.cubic_to(x, y + ky, x + kx, y, x + rx, y);
+    b.close();
+    b.finish()
+}
+
+// ===== Paths ==================================================================
+
+/// Converts path commands to absolute lines and curves, arcs become cubic curves.
+#[derive(Default)]
+struct Cursor {
+    current: (f64, f64),
+    /// start of the subpath
+    start: (f64, f64),
+    /// second control point of the previous cubic curve, reflected by `S`
+    cubic: Option<(f64, f64)>,
+    /// control point of the previous quadratic curve, reflected by `T`
+    quadratic: Option<(f64, f64)>,
+}
+
+impl Cursor {
+    fn commands(&mut self, commands: &[Command], b: &mut PathBuilder) -> Result<(), ParseError> {
+        for command in commands {
+            if let Command::Raw(raw) = command {
+                self.commands(&path_parser::parse(raw)?, b)?;
+                continue;
+            }
+            if !self.command(command, b) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns false if the rest of the path can't be rendered
+    fn command(&mut self, command: &Command, b: &mut PathBuilder) -> bool {
+        use Command::*;
+        let (cx, cy) = self.current;
+        // the coordinates of relative commands are offsets from the current point
+        let origin = match command {
+            MoveToRelative(_)
+            | LineRelative(_)
+            | HorizontalLineRelative(_)
+            | VerticalLineRelative(_)
+            | CubicBezierRelative(_)
+            | CubicBezierExtendedRelative(_)
+            | QuadraticBezierRelative(_)
+            | QuadraticBezierExtendedRelative(_)
+            | ArcRelative(_) => (cx, cy),
+            _ => (0., 0.),
+        };
+        let at = |c: &Coord| (origin.0 + c.0.0, origin.1 + c.1.0);
+        let (mut cubic, mut quadratic) = (None, None);
+        let end = match command {
+            MoveTo(c) | MoveToRelative(c) => {
+                let to = at(c);
+                b.move_to(to.0 as f32, to.1 as f32);
+                self.start = to;
+                to
+            }
+            Line(c) | LineRelative(c) => self.line(at(c), b),
+            HorizontalLine(x) | HorizontalLineRelative(x) => self.line((origin.0 + x.0, cy), b),
+            VerticalLine(y) | VerticalLineRelative(y) => self.line((cx, origin.1 + y.0), b),
+            CubicBezier(args) | CubicBezierRelative(args) => {
+                cubic = Some(at(&args.p2));
+                self.cubic(at(&args.p1), at(&args.p2), at(&args.end), b)
+            }
+            CubicBezierExtended(args) | CubicBezierExtendedRelative(args) => {
+                let p1 = reflect(self.cubic, self.current);
+                cubic = Some(at(&args.p2));
+                self.cubic(p1, at(&args.p2), at(&args.end), b)
+            }
+            QuadraticBezier(args) | QuadraticBezierRelative(args) => {
+                quadratic = Some(at(&args.p1));
+                self.quadratic(at(&args.p1), at(&args.end), b)
+            }
+            QuadraticBezierExtended(c) | QuadraticBezierExtendedRelative(c) => {
+                let p1 = reflect(self.quadratic, self.current);
+                quadratic = Some(p1);
+                self.quadratic(p1, at(c), b)
+            }
+            Arc(args) | ArcRelative(args) => self.arc(args, at(&args.end), b),
+            ClosePath => {
+                b.close();
+                self.start
+            }
+            Raw(_) | Invalid => return false,
+        };
+        self.current = end;
+        self.cubic = cubic;
+        self.quadratic = quadratic;
+        true
+    }
+
+    fn line(&self, to: (f64, f64), b: &mut PathBuilder) -> (f64, f64) {
+        b.line_to(to.0 as f32, to.1 as f32);
+        to
+    }
+
+    fn cubic(
+        &self,
+        p1: (f64, f64),
+        p2: (f64, f64),
+        to: (f64, f64),
+        b: &mut PathBuilder,
+    ) -> (f64, f64) {
+        b.cubic_to(
+            p1.0 as f32,
+            p1.1 as f32,
+            p2.0 as f32,
+            p2.1 as f32,
+            to.0 as f32,
+            to.1 as f32,
+        );
+        to
+    }
+
+    fn quadratic(&self, p1: (f64, f64), to: (f64, f64), b: &mut PathBuilder) -> (f64, f64) {
+        b.quad_to(p1.0 as f32, p1.1 as f32, to.0 as f32, to.1 as f32);
+        to
+    }
+
+    /// Approximates the arc with a cubic curve per quarter turn, with the center
+    /// parameterization of the svg spec (appendix B.2.4).
+    fn arc(&self, args: &ArcArgs, to: (f64, f64), b: &mut PathBuilder) -> (f64, f64) {
+        let (x1, y1) = self.current;
+        let (x2, y2) = to;
+        if (x1, y1) == (x2, y2) {
+            return to;
+        }
+        let (mut rx, mut ry) = (args.rx.abs(), args.ry.abs());
+        if rx == 0. || ry == 0. {
+            return self.line(to, b);
+        }
+        let (sin, cos) = args.x_axis_rotation.to_radians().sin_cos();
+        let (dx, dy) = ((x1 - x2) / 2., (y1 - y2) / 2.);
+        let x1p = cos * dx + sin * dy;
+        let y1p = -sin * dx + cos * dy;
+
+        // radii that are too small are scaled up
+        let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+        if lambda > 1. {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let numerator = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+        let denominator = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+        let mut coef = (numerator / denominator).max(0.).sqrt();
+        if args.large_arc == args.sweep {
+            coef = -coef;
+        }
+        let cxp = coef * rx * y1p / ry;
+        let cyp = -coef * ry * x1p / rx;
+        let center_x = cos * cxp - sin * cyp + (x1 + x2) / 2.;
+        let center_y = sin * cxp + cos * cyp + (y1 + y2) / 2.;
+
+        let angle =
+            |ux: f64, uy: f64, vx: f64, vy: f64| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+        let (ux, uy) = ((x1p - cxp) / rx, (y1p - cyp) / ry);
+        let (vx, vy) = ((-x1p - cxp) / rx, (-y1p - cyp) / ry);
+        let start = angle(1., 0., ux, uy);
+        let mut sweep = angle(ux, uy, vx, vy);
+        if !args.sweep && sweep > 0. {
+            sweep -= std::f64::consts::TAU;
+        } else if args.sweep && sweep < 0. {
+            sweep += std::f64::consts::TAU;
+        }
+
+        let segments = (sweep.abs() / FRAC_PI_2).ceil().max(1.);
+        let delta = sweep / segments;
+        let k = 4. / 3. * (delta / 4.).tan();
+        let point = |t: f64| {
+            let (s, c) = t.sin_cos();
+            (
+                center_x + rx * c * cos - ry * s * sin,
+                center_y + rx * c * sin + ry * s * cos,
+            )
+        };
+        let derivative = |t: f64| {
+            let (s, c) = t.sin_cos();
+            (-rx * s * cos - ry * c * sin, -rx * s * sin + ry * c * cos)
+        };
+        for i in 0..segments as usize {
+            let t0 = start + delta * i as f64;
+            let t1 = t0 + delta;
+            let (p0, d0) = (point(t0), derivative(t0));
+            let (p3, d1) = (point(t1), derivative(t1));
+            let end = if i + 1 == segments as usize { to } else { p3 };
+            self.cubic(
+                (p0.0 + k * d0.0, p0.1 + k * d0.1),
+                (p3.0 - k * d1.0, p3.1 - k * d1.1),
+                end,
+                b,
+            );
+        }
+        to
+    }
+}
+
+/// The control point reflected around the current point, the current point if there is no
+/// previous curve of the same kind.
+fn reflect(control: Option<(f64, f64)>, current: (f64, f64)) -> (f64, f64) {
+    match control {
+        Some((x, y)) => (2. * current.0 - x, 2. * current.1 - y),
+        None => current,
+    }
+}
+
+// ===== Properties =============================================================
+
+#[derive(Debug, Clone)]
+enum PaintValue {
+    None,
+    Color(tiny_skia::Color),
+    /// id of a gradient and the paint used if it doesn't exist
+    Url(String, Box<PaintValue>),
+}
+
+/// The inherited properties
+#[derive(Debug, Clone)]
+struct Props {
+    color: tiny_skia::Color,
+    fill: PaintValue,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    stroke: PaintValue,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: f32,
+    dasharray: Option<Vec<f32>>,
+    dashoffset: f32,
+}
+
+impl Default for Props {
+    fn default() -> Self {
+        Self {
+            color: tiny_skia::Color::BLACK,
+            fill: PaintValue::Color(tiny_skia::Color::BLACK),
+            fill_opacity: 1.,
+            fill_rule: FillRule::Winding,
+            stroke: PaintValue::None,
+            stroke_opacity: 1.,
+            stroke_width: 1.,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 4.,
+            dasharray: None,
+            dashoffset: 0.,
+        }
+    }
+}
+
+impl Props {
+    /// Applies the declarations of an element, invalid values are ignored so the inherited
+    /// value is kept.
+    fn apply(&mut self, declarations: &[(&str, &str)]) {
+        // currentColor is the color of the element
+        if let Some(color) = property(declarations, "color").and_then(color) {
+            self.color = color;
+        }
+        for (name, value) in declarations {
+            let value = value.trim();
+            match *name {
+                "fill" => self.fill = self.paint(value).unwrap_or(self.fill.clone()),
+                "stroke" => self.stroke = self.paint(value).unwrap_or(self.stroke.clone()),
+                "fill-opacity" => self.fill_opacity = fraction(value).unwrap_or(self.fill_opacity),
+                "stroke-opacity" => {
+                    self.stroke_opacity = fraction(value).unwrap_or(self.stroke_opacity)
+                }
+                "fill-rule" => match value {
+                    "nonzero" => self.fill_rule = FillRule::Winding,
+                    "evenodd" => self.fill_rule = FillRule::EvenOdd,
+                    _ => {}
+                },
+                "stroke-width" => {
+                    if let Some(width) = length(value, 1.).filter(|w| *w >= 0.) {
+                        self.stroke_width = width;
+                    }
+                }
+                "stroke-linecap" => match value {
+                    "butt" => self.line_cap = LineCap::Butt,
+                    "round" => self.line_cap = LineCap::Round,
+                    "square" => self.line_cap = LineCap::Square,
+                    _ => {}
+                },
+                "stroke-linejoin" => match value {
+                    "miter" => self.line_join = LineJoin::Miter,
+                    "round" => self.line_join = LineJoin::Round,
+                    "bevel" => self.line_join = LineJoin::Bevel,
+                    _ => {}
+                },
+                "stroke-miterlimit" => {
+                    if let Some(limit) = value.parse::<f32>().ok().filter(|l| *l >= 1.) {
+                        self.miter_limit = limit;
+                    }
+                }
+                "stroke-dasharray" if value.eq_ignore_ascii_case("none") => self.dasharray = None,
+                "stroke-dasharray" => {
+                    let array = value
+                        .split(|c: char| c.is_whitespace() || c == ',')
+                        .filter(|s| !s.is_empty())
+                        .map(|v| length(v, 1.).filter(|l| *l >= 0.))
+                        .collect::<Option<Vec<_>>>();
+                    if let Some(array) = array {
+                        // a pattern of only gaps is a solid line
+                        self.dasharray = Some(array).filter(|a| a.iter().sum::<f32>() > 0.);
+                    }
+                }
+                "stroke-dashoffset" => {
+                    self.dashoffset = length(value, 1.).unwrap_or(self.dashoffset)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn paint(&self, value: &str) -> Option<PaintValue> {
+        if value.eq_ignore_ascii_case("none") {
+            return Some(PaintValue::None);
+        }
+        if value.eq_ignore_ascii_case("currentColor") {
+            return Some(PaintValue::Color(self.color));
+        }
+        if let Some(rest) = value.strip_prefix("url(") {
+            let (url, fallback) = rest.split_once(')')?;
+            let id = url.trim().trim_matches(['"', '\'']).strip_prefix('#')?;
+            let fallback = match fallback.trim() {
+                "" => PaintValue::None,
+                fallback => self.paint(fallback)?,
+            };
+            return Some(PaintValue::Url(id.to_string(), Box::new(fallback)));
+        }
+        color(value).map(PaintValue::Color)
+    }
+}
+
+/// The presentation attributes and the declarations of the style attribute of an element, in
+/// order of precedence.
+fn declarations<'a>(node: Node<'a, '_>) -> Vec<(&'a str, &'a str)> {
+    let mut declarations = node
+        .attributes()
+        .filter(|a| a.namespace().is_none() && a.name() != "style")
+        .map(|a| (a.name(), a.value()))
+        .collect::<Vec<_>>();
+    if let Some(style) = node.attribute("style") {
+        declarations.extend(style.split(';').filter_map(|d| {
+            let (name, value) = d.split_once(':')?;
+            Some((name.trim(), value.trim()))
+        }));
+    }
+    declarations
+        .into_iter()
+        .filter(|(_, value)| !value.starts_with("var(") && *value != "inherit")
+        .collect()
+}
+
+/// The value with the highest precedence
+fn property<'a>(declarations: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    declarations
+        .iter()
+        .rev()
+        .find(|(n, _)| *n == name)
+        .map(|(_, value)| *value)
+}
+
+// ===== Values =================================================================
+
+fn transform(value: &str) -> Option<Transform> {
+    let mut ts = Transform::identity();
+    for transform in parse::transforms(value)? {
+        let t = |a: f64| a as f32;
+        let next = match transform {
+            element::Transform::Translate(x, y) => Transform::from_translate(t(x), t(y)),
+            element::Transform::TranslateX(x) => Transform::from_translate(t(x), 0.),
+            element::Transform::TranslateY(y) => Transform::from_translate(0., t(y)),
+            element::Transform::Scale(x, y) => Transform::from_scale(t(x), t(y)),
+            element::Transform::ScaleX(x) => Transform::from_scale(t(x), 1.),
+            element::Transform::ScaleY(y) => Transform::from_scale(1., t(y)),
+            element::Transform::ScaleXY(xy) => Transform::from_scale(t(xy), t(xy)),
+            element::Transform::Rotate(a) => Transform::from_rotate(t(a)),
+            element::Transform::RotateXY(a, x, y) => Transform::from_rotate_at(t(a), t(x), t(y)),
+            element::Transform::SkewX(a) => Transform::from_skew(t(a.to_radians().tan()), 0.),
+            element::Transform::SkewY(a) => Transform::from_skew(0., t(a.to_radians().tan())),
+            element::Transform::Matrix(a, b, c, d, e, f) => {
+                Transform::from_row(t(a), t(b), t(c), t(d), t(e), t(f))
+            }
+        };
+        ts = ts.pre_concat(next);
+    }
+    Some(ts)
+}
+
+/// A length in user units, percentages are relative to `reference`. Units other than px
+/// aren't supported.
+fn length(value: &str, reference: f32) -> Option<f32> {
+    let value = value.trim();
+    match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok().map(|p| p / 100. * reference),
+        None => user_unit(value),
+    }
+}
+
+fn user_unit(value: &str) -> Option<f32> {
+    let value = value.trim();
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// A number or percentage clamped to 0..=1, like opacities and offsets
+fn fraction(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let fraction = match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok()? / 100.,
+        None => value.parse().ok()?,
+    };
+    Some(fraction.clamp(0., 1.))
+}
+
+/// Named, hex, rgb(a) and oklch colors of css.
+fn color(value: &str) -> Option<tiny_skia::Color> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        return hex_color(hex);
+    }
+    if let Some((function, args)) = value.split_once('(') {
+        let args = args.strip_suffix(')')?;
+        let (args, alpha) = match args.split_once('/') {
+            Some((args, alpha)) => (args, fraction(alpha)?),
+            None => (args, 1.),
+        };
+        let args = args
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        return match (function.trim(), args.as_slice()) {
+            ("rgb" | "rgba", [r, g, b, rest @ ..]) if rest.len() <= 1 => {
+                let channel = |v: &str| match v.strip_suffix('%') {
+                    Some(p) => p.parse::<f32>().ok().map(|p| p / 100.),
+                    None => v.parse::<f32>().ok().map(|v| v / 255.),
+                };
+                let alpha = match rest {
+                    [a] => fraction(a)?,
+                    _ => alpha,
+                };
+                let clamp = |v: f32| v.clamp(0., 1.);
+                tiny_skia::Color::from_rgba(
+                    clamp(channel(r)?),
+                    clamp(channel(g)?),
+                    clamp(channel(b)?),
+                    alpha,
+                )
+            }
+            ("oklch", [l, c, h]) => {
+                let l = match l.strip_suffix('%') {
+                    Some(p) => p.parse::<f64>().ok()? / 100.,
+                    None => l.parse().ok()?,
+                };
+                let c = c.parse::<f64>().ok()?;
+                let h = h.strip_suffix("deg").unwrap_or(h).parse::<f64>().ok()?;
+                let [r, g, b] = oklch_to_srgb(l, c, h);
+                tiny_skia::Color::from_rgba(r, g, b, alpha)
+            }
+            _ => None,
+        };
+    }
+    let name = value.to_ascii_lowercase();
+    if name == "transparent" {
+        return Some(tiny_skia::Color::TRANSPARENT);
+    }
+    let [r, g, b] = named_color(&name)?;
+    Some(tiny_skia::Color::from_rgba8(r, g, b, 255))
+}
+
+fn hex_color(hex: &str) -> Option<tiny_skia::Color> {
+    let digits = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<_>>>()?;
+    let [r, g, b, a] = match *digits.as_slice() {
+        [r, g, b] => [r * 17, g * 17, b * 17, 255],
+        [r, g, b, a] => [r * 17, g * 17, b * 17, a * 17],
+        [r1, r2, g1, g2, b1, b2] => [r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, 255],
+        [r1, r2, g1, g2, b1, b2, a1, a2] => {
+            [r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, a1 * 16 + a2]
+        }
+        _ => return None,
+    };
+    Some(tiny_skia::Color::from_rgba8(r, g, b, a))
+}
+
+/// Converts to srgb through oklab, colors outside of srgb are clipped
+fn oklch_to_srgb(l: f64, c: f64, h: f64) -> [f32; 3] {
+    let (sin, cos) = h.to_radians().sin_cos();
+    let (a, b) = (c * cos, c * sin);
+    let l_ = (l + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m_ = (l - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s_ = (l - 0.089_484_177_5 * a - 1.291_485_548 * b).powi(3);
+    let linear = [
+        4.076_741_662_1 * l_ - 3.307_711_591_3 * m_ + 0.230_969_929_2 * s_,
+        -1.268_438_004_6 * l_ + 2.609_757_401_1 * m_ - 0.341_319_396_5 * s_,
+        -0.004_196_086_3 * l_ - 0.703_418_614_7 * m_ + 1.707_614_701 * s_,
+    ];
+    linear.map(|v| {
+        let v = v.clamp(0., 1.);
+        let srgb = if v <= 0.003_130_8 {
+            12.92 * v
+        } else {
+            1.055 * v.powf(1. / 2.4) - 0.055
+        };
+        srgb as f32
+    })
+}
+
+// ===== Viewports ==============================================================
+
+/// The viewBox attribute of an svg
+fn viewbox(node: Node) -> Option<Viewport> {
+    match parse::numbers(node.attribute("viewBox")?)?.as_slice() {
+        &[x, y, w, h] => Viewport::new(x as f32, y as f32, w as f32, h as f32),
+        _ => None,
+    }
+}
+
+struct Viewport(NonZeroRect);
+
+impl Viewport {
+    fn new(x: f32, y: f32, w: f32, h: f32) -> Option<Self> {
+        NonZeroRect::from_xywh(x, y, w, h).map(Viewport)
+    }
+
+    fn width(&self) -> f32 {
+        self.0.width()
+    }
+
+    fn height(&self) -> f32 {
+        self.0.height()
+    }
+
+    /// Maps the viewport to a `width` x `height` area
+    fn transform(&self, width: f32, height: f32, align: Align) -> Transform {
+        let (sx, sy) = (width / self.width(), height / self.height());
+        let Some((ax, ay, slice)) = align.0 else {
+            return Transform::from_scale(sx, sy).pre_translate(-self.0.x(), -self.0.y());
+        };
+        let s = if slice { sx.max(sy) } else { sx.min(sy) };
+        let tx = ax * (width - self.width() * s);
+        let ty = ay * (height - self.height() * s);
+        Transform::from_row(s, 0., 0., s, tx, ty).pre_translate(-self.0.x(), -self.0.y())
+    }
+}
+
+/// preserveAspectRatio: the alignment of both axes and slice, `None` stretches the viewBox
+#[derive(Clone, Copy)]
+struct Align(Option<(f32, f32, bool)>);
+
+impl Default for Align {
+    /// xMidYMid meet
+    fn default() -> Self {
+        Align(Some((0.5, 0.5, false)))
+    }
+}
+
+impl Align {
+    fn parse(value: &str) -> Self {
+        let mut parts = value.split_whitespace();
+        let Some(align) = parts.next() else {
+            return Align::default();
+        };
+        if align == "none" {
+            return Align(None);
+        }
+        let axis = |name: &str| match name {
+            "Min" => Some(0.),
+            "Mid" => Some(0.5),
+            "Max" => Some(1.),
+            _ => None,
+        };
+        let (Some(x), Some(y)) = (
+            align.get(1..4).and_then(axis),
+            align.get(5..8).and_then(axis),
+        ) else {
+            return Align::default();
+        };
+        Align(Some((x, y, parts.next() == Some("slice"))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::{
+        Parent,
+        color::Color,
+        style::{FillRule as Rule, LineCap as Cap},
+        units::Length,
+    };
+
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const NONE: [u8; 4] = [0, 0, 0, 0];
+
+    fn svg() -> Element<Svg> {
+        Element::svg().size(20, 20).viewbox(0, 0, 20, 20)
+    }
+
+    #[test]
+    fn fill_rules() {
+        // two squares drawn in the same direction, the inner one is a hole with evenodd
+        let d = "M2,2 H18 V18 H2 Z M6,6 H14 V14 H6 Z";
+        let image = |rule| {
+            svg()
+                .push(
+                    Element::path()
+                        .push_path(Command::Raw(d.to_string()))
+                        .fill(Color::Red)
+                        .fill_rule(rule),
+                )
+                .rasterize(None)
+                .unwrap()
+        };
+        let nonzero = image(Rule::NonZero);
+        assert_eq!(nonzero.pixel(10, 10), Some(RED));
+        assert_eq!(nonzero.pixel(4, 4), Some(RED));
+        let evenodd = image(Rule::EvenOdd);
+        assert_eq!(evenodd.pixel(10, 10), Some(NONE));
+        assert_eq!(evenodd.pixel(4, 4), Some(RED));
+    }
+
+    #[test]
+    fn strokes() {
+        let line = |cap| {
+            Element::line(5, 10, 15, 10)
+                .stroke(Color::CssName("blue".into()))
+                .stroke_width(Length::from(4_u32))
+                .stroke_linecap(cap)
+        };
+        let butt = svg().push(line(Cap::Butt)).rasterize(None).unwrap();
+        assert_eq!(butt.pixel(10, 9), Some(BLUE));
+        assert_eq!(butt.pixel(10, 13), Some(NONE));
+        assert_eq!(butt.pixel(3, 10), Some(NONE));
+        let square = svg().push(line(Cap::Square)).rasterize(None).unwrap();
+        assert_eq!(square.pixel(3, 10), Some(BLUE));
+
+        let dashed = svg()
+            .push(line(Cap::Butt).stroke_dasharray([4]))
+            .rasterize(None)
+            .unwrap();
+        assert_eq!(dashed.pixel(6, 10), Some(BLUE));
+        assert_eq!(dashed.pixel(10, 10), Some(NONE));
+        assert_eq!(dashed.pixel(14, 10), Some(BLUE));
+    }
+
+    #[test]
+    fn color_keywords() {
+        let image = svg()
+            .push(
+                Element::group()
+                    .attr("color", "SteelBlue")
+                    .push(Element::rect(0, 0, 10, 20).attr("fill", "CurrentColor"))
+                    .push(Element::rect(10, 0, 10, 20).attr("fill", "NONE")),
+            )
+            .rasterize(None)
+            .unwrap();
+        assert_eq!(image.pixel(5, 10), Some([70, 130, 180, 255]));
+        assert_eq!(image.pixel(15, 10), Some(NONE));
+    }
+
+    #[test]
+    fn transforms_and_viewbox() {
+        let image = Element::svg()
+            .size(40, 20)
+            .viewbox(0, 0, 10, 10)
+            .push(
+                Element::rect(0, 0, 5, 5)
+                    .fill(Color::Red)
+                    .transform(element::Transform::Translate(5., 5.)),
+            )
+            .rasterize(None)
+            .unwrap();
+        // the viewbox is scaled by 2 and centered
+        assert_eq!(image.pixel(25, 15), Some(RED));
+        assert_eq!(image.pixel(15, 5), Some(NONE));
+        assert_eq!(image.pixel(5, 15), Some(NONE));
+    }
+
+    #[test]
+    fn group_opacity() {
+        let image = svg()
+            .push(
+                Element::group()
+                    .attr("opacity", 0.5)
+                    .push(Element::rect(0, 0, 20, 20).fill(Color::Red))
+                    .push(Element::rect(0, 0, 10, 20).fill(Color::CssName("blue".into()))),
+            )
+            .rasterize(None)
+            .unwrap();
+        // the blue rect covers the red one before the group is made transparent
+        let [r, _, b, a] = image.pixel(5, 5).unwrap();
+        assert_eq!((r, b), (0, 255));
+        assert!(a.abs_diff(128) <= 1, "{a}");
+        assert_eq!(image.pixel(15, 5).unwrap()[0], 255);
+    }
+
+    #[test]
+    fn gradients() {
+        let image = svg()
+            .def(
+                Element::generic("linearGradient")
+                    .id("fade")
+                    .push(
+                        Element::generic("stop")
+                            .attr("offset", "0")
+                            .attr("stop-color", "red"),
+                    )
+                    .push(
+                        Element::generic("stop")
+                            .attr("offset", "100%")
+                            .attr("stop-color", "#00f"),
+                    ),
+            )
+            .push(Element::rect(0, 0, 20, 20).fill(Color::Url("fade".into())))
+            .push(Element::rect(0, 0, 20, 2).fill(Color::Url("missing".into())))
+            .rasterize(None)
+            .unwrap();
+        let [r, _, b, _] = image.pixel(1, 10).unwrap();
+        assert!(r > 230 && b < 25, "{r} {b}");
+        let [r, _, b, _] = image.pixel(18, 10).unwrap();
+        assert!(r < 25 && b > 230, "{r} {b}");
+        // a missing gradient without a fallback is not painted
+        assert_eq!(image.pixel(18, 0), image.pixel(18, 5));
+    }
+
+    #[test]
+    fn use_references() {
+        let image = svg()
+            .def(Element::rect(0, 0, 5, 5).id("dot").fill(Color::Red))
+            .push(Element::use_href(10, 10).href("dot"))
+            .rasterize(None)
+            .unwrap();
+        assert_eq!(image.pixel(2, 2), Some(NONE));
+        assert_eq!(image.pixel(12, 12), Some(RED));
+    }
+
+    #[test]
+    fn arcs() {
+        // a full circle of radius 8 from two arcs
+        let image = svg()
+            .push(
+                Element::path()
+                    .push_path(Command::Raw(
+                        "M2,10 a8,8 0 1,1 16,0 a8,8 0 1,1 -16,0 z".into(),
+                    ))
+                    .fill(Color::CssName("blue".into())),
+            )
+            .rasterize(None)
+            .unwrap();
+        for (x, y) in [(10, 3), (10, 16), (3, 10), (16, 10), (10, 10)] {
+            assert_eq!(image.pixel(x, y), Some(BLUE), "{x},{y}");
+        }
+        for (x, y) in [(3, 3), (16, 16), (10, 0), (10, 19)] {
+            assert_eq!(image.pixel(x, y), Some(NONE), "{x},{y}");
+        }
+    }
+
+    #[test]
+    fn invalid_path_data() {
+        let err = svg()
+            .push(Element::path().push_path(Command::Raw("M2,2 L x".into())))
+            .rasterize(None)
+            .unwrap_err();
+        assert!(
+            matches!(&err, RasterError::Path(d, _) if d == "M2,2 L x"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn png() {
+        let image = svg()
+            .push(Element::circle(10, 10, 6).fill(Color::Rgb(10, 200, 30)))
+            .rasterize(None)
+            .unwrap();
+        let png = image.encode_png().unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        let decoded = Image::from_png(&png).unwrap();
+        assert!(decoded.diff(&image, 0).unwrap().is_empty());
+        assert_eq!(image.to_rgba().len(), 20 * 20 * 4);
+
+        let moved = svg()
+            .push(Element::circle(11, 10, 6).fill(Color::Rgb(10, 200, 30)))
+            .rasterize(None)
+            .unwrap();
+        let diff = moved.diff(&image, 8).unwrap();
+        assert!(diff.pixels > 0);
+        assert_eq!(diff.image.pixel(16, 10), Some(RED));
+        let smaller = Element::svg().size(10, 10).rasterize(None).unwrap();
+        assert!(image.diff(&smaller, 0).is_none());
+    }
+
+    #[rstest]
+    #[case("red", Some([255, 0, 0, 255]))]
+    #[case("steelblue", Some([70, 130, 180, 255]))]
+    #[case("RebeccaPurple", Some([102, 51, 153, 255]))]
+    #[case("transparent", Some([0, 0, 0, 0]))]
+    #[case("bluish", None)]
+    #[case("#0f08", Some([0, 255, 0, 136]))]
+    #[case("#123456", Some([0x12, 0x34, 0x56, 255]))]
+    #[case("rgb(1 2 3)", Some([1, 2, 3, 255]))]
+    #[case("rgba(1, 2, 3, 0.5)", Some([1, 2, 3, 128]))]
+    #[case("rgb(100% 0% 0% / 50%)", Some([255, 0, 0, 128]))]
+    #[case("oklch(1 0 0)", Some([255, 255, 255, 255]))]
+    #[case("oklch(0.628 0.2577 29.23)", Some([255, 0, 0, 255]))]
+    #[case("hsl(0 100% 50%)", None)]
+    #[case("#12", None)]
+    fn colors(#[case] value: &str, #[case] expected: Option<[u8; 4]>) {
+        let rgba = color(value).map(|c| {
+            let c = c.to_color_u8();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        });
+        assert_eq!(rgba, expected);
+    }
+}
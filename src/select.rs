@@ -0,0 +1,583 @@
+// ===== Selectors ==============================================================
+//
+// A subset of css selectors to query element trees: type, universal, id, class and attribute
+// selectors, `:nth-child()`, `:first-child` and `:last-child`, the child and descendant
+// combinators and selector lists.
+//
+// Attribute selectors match the attributes as they are rendered, ie `[width='1']` and not
+// `[width='1px']`. The properties of the style attribute match like presentation attributes,
+// so `[fill=red]` matches `style="fill: red;"`.
+//
+// Positions count the child elements of the parent, character data is skipped. The
+// definitions of an svg are counted as its first children and are not wrapped in a `<defs>`
+// element like they are when rendered.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{element::Element, marker_traits::ElementKind, node::Node, visit::Visit};
+
+/// A parsed selector, see [`Element::select`] to query a tree with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    /// selector list, matches if any of the selectors matches
+    complex: Vec<Complex>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorError {
+    /// byte offset in the selector
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for SelectorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+/// Compound selectors separated by combinators, `combinators[i]` is between `compounds[i]` and
+/// `compounds[i + 1]`.
+#[derive(Debug, Clone, PartialEq)]
+struct Complex {
+    compounds: Vec<Compound>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Compound {
+    /// `None` for `*`
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attrs: Vec<(String, Option<String>)>,
+    positions: Vec<Position>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Position {
+    /// `An+B`
+    Nth(i32, i32),
+    Last,
+}
+
+/// 1-based position of an element and the number of elements of its parent
+#[derive(Debug, Clone, Copy)]
+struct Sibling {
+    position: usize,
+    count: usize,
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser { s, pos: 0 }.selector()
+    }
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        selector.parse()
+    }
+
+    /// `ancestors` are the parents of the node starting at the root
+    fn matches(&self, node: (&dyn Node, Sibling), ancestors: &[(&dyn Node, Sibling)]) -> bool {
+        self.complex
+            .iter()
+            .any(|c| c.matches(c.compounds.len() - 1, node, ancestors))
+    }
+}
+
+impl Complex {
+    /// Matches the compounds up to `last` with the node as the subject
+    fn matches(
+        &self,
+        last: usize,
+        node: (&dyn Node, Sibling),
+        ancestors: &[(&dyn Node, Sibling)],
+    ) -> bool {
+        if !self.compounds[last].matches(node.0, node.1) {
+            return false;
+        }
+        if last == 0 {
+            return true;
+        }
+        match self.combinators[last - 1] {
+            Combinator::Child => ancestors
+                .split_last()
+                .is_some_and(|(parent, rest)| self.matches(last - 1, *parent, rest)),
+            Combinator::Descendant => (0..ancestors.len())
+                .rev()
+                .any(|i| self.matches(last - 1, ancestors[i], &ancestors[..i])),
+        }
+    }
+}
+
+impl Compound {
+    fn matches(&self, node: &dyn Node, sibling: Sibling) -> bool {
+        self.tag.as_ref().is_none_or(|tag| tag == node.tag())
+            && self.id.as_ref().is_none_or(|id| node.get_id() == Some(id))
+            && self.classes.iter().all(|class| {
+                node.get_class()
+                    .is_some_and(|c| c.split_whitespace().any(|c| c == class))
+            })
+            && (self.attrs.is_empty() || {
+                let attributes = attributes(node);
+                self.attrs.iter().all(|(name, value)| {
+                    attributes
+                        .iter()
+                        .any(|(n, actual)| n == name && value.as_ref().is_none_or(|v| v == actual))
+                })
+            })
+            && self.positions.iter().all(|p| p.matches(sibling))
+    }
+}
+
+/// The rendered attributes of the node and the properties of its style attribute
+fn attributes(node: &dyn Node) -> Vec<(String, String)> {
+    let mut attributes = node.attributes();
+    let properties = attributes
+        .iter()
+        .filter(|(name, _)| name == "style")
+        .flat_map(|(_, style)| style.split(';'))
+        .filter_map(|declaration| declaration.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect::<Vec<_>>();
+    attributes.extend(properties);
+    attributes
+}
+
+impl Position {
+    fn matches(&self, sibling: Sibling) -> bool {
+        match *self {
+            Position::Last => sibling.position == sibling.count,
+            Position::Nth(a, b) => {
+                let offset = sibling.position as i32 - b;
+                match a {
+                    0 => offset == 0,
+                    a => offset % a == 0 && offset / a >= 0,
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn selector(&mut self) -> Result<Selector, SelectorError> {
+        let mut complex = vec![self.complex()?];
+        while self.eat(',') {
+            complex.push(self.complex()?);
+        }
+        match self.peek() {
+            None => Ok(Selector { complex }),
+            Some(c) => Err(self.error(format!("unexpected '{c}'"))),
+        }
+    }
+
+    fn complex(&mut self) -> Result<Complex, SelectorError> {
+        self.skip_whitespace();
+        let mut compounds = vec![self.compound()?];
+        let mut combinators = vec![];
+        loop {
+            let whitespace = self.skip_whitespace();
+            let combinator = if self.eat('>') {
+                self.skip_whitespace();
+                Combinator::Child
+            } else if whitespace && self.peek().is_some_and(|c| c != ',') {
+                Combinator::Descendant
+            } else {
+                break;
+            };
+            combinators.push(combinator);
+            compounds.push(self.compound()?);
+        }
+        Ok(Complex {
+            compounds,
+            combinators,
+        })
+    }
+
+    fn compound(&mut self) -> Result<Compound, SelectorError> {
+        let start = self.pos;
+        let mut compound = Compound::default();
+        if !self.eat('*') && self.peek().is_some_and(is_ident) {
+            compound.tag = Some(self.ident()?);
+        }
+        loop {
+            if self.eat('#') {
+                compound.id = Some(self.ident()?);
+            } else if self.eat('.') {
+                compound.classes.push(self.ident()?);
+            } else if self.eat('[') {
+                compound.attrs.push(self.attr()?);
+            } else if self.eat(':') {
+                compound.positions.push(self.pseudo_class()?);
+            } else {
+                break;
+            }
+        }
+        if self.pos == start {
+            return Err(self.error("expected a selector".to_string()));
+        }
+        Ok(compound)
+    }
+
+    /// `[name]` or `[name=value]` with an optionally quoted value, the `[` is consumed.
+    fn attr(&mut self) -> Result<(String, Option<String>), SelectorError> {
+        self.skip_whitespace();
+        let name = self.ident()?;
+        self.skip_whitespace();
+        let value = if self.eat('=') {
+            self.skip_whitespace();
+            Some(match self.peek() {
+                Some(quote @ ('"' | '\'')) => {
+                    self.pos += 1;
+                    let rest = &self.s[self.pos..];
+                    let end = rest
+                        .find(quote)
+                        .ok_or_else(|| self.error("unterminated string".to_string()))?;
+                    self.pos += end + 1;
+                    rest[..end].to_string()
+                }
+                _ => self.ident()?,
+            })
+        } else {
+            None
+        };
+        self.skip_whitespace();
+        self.expect(']')?;
+        Ok((name, value))
+    }
+
+    fn pseudo_class(&mut self) -> Result<Position, SelectorError> {
+        let start = self.pos;
+        match self.ident()?.as_str() {
+            "first-child" => Ok(Position::Nth(0, 1)),
+            "last-child" => Ok(Position::Last),
+            "nth-child" => {
+                self.expect('(')?;
+                let end = self.s[self.pos..]
+                    .find(')')
+                    .ok_or_else(|| self.error("expected ')'".to_string()))?;
+                let arg = &self.s[self.pos..self.pos + end];
+                let nth =
+                    parse_nth(arg).ok_or_else(|| self.error(format!("invalid nth '{arg}'")))?;
+                self.pos += end + 1;
+                Ok(nth)
+            }
+            other => {
+                self.pos = start;
+                Err(self.error(format!("unsupported pseudo class ':{other}'")))
+            }
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, SelectorError> {
+        let len = self.s[self.pos..]
+            .find(|c| !is_ident(c))
+            .unwrap_or(self.s.len() - self.pos);
+        if len == 0 {
+            return Err(self.error("expected a name".to_string()));
+        }
+        let ident = &self.s[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(ident.to_string())
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += c.len_utf8();
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SelectorError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{c}'")))
+        }
+    }
+
+    /// Returns true if any whitespace was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+        self.pos > start
+    }
+
+    fn error(&self, message: String) -> SelectorError {
+        SelectorError {
+            position: self.pos,
+            message,
+        }
+    }
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_')
+}
+
+/// Parses the `An+B` argument of `:nth-child()`, like `2n+1`, `-n+3`, `odd` or `4`.
+fn parse_nth(arg: &str) -> Option<Position> {
+    let arg = arg.trim().to_ascii_lowercase().replace(' ', "");
+    match arg.as_str() {
+        "odd" => return Some(Position::Nth(2, 1)),
+        "even" => return Some(Position::Nth(2, 0)),
+        _ => {}
+    }
+    let Some((a, b)) = arg.split_once('n') else {
+        return arg.parse().ok().map(|b| Position::Nth(0, b));
+    };
+    let a = match a {
+        "" | "+" => 1,
+        "-" => -1,
+        a => a.parse().ok()?,
+    };
+    let b = match b {
+        "" => 0,
+        b if b.starts_with(['+', '-']) => b.trim_start_matches('+').parse().ok()?,
+        _ => return None,
+    };
+    Some(Position::Nth(a, b))
+}
+
+// ===== Queries ================================================================
+
+impl<T: ElementKind + Visit + 'static> Element<T> {
+    /// Returns the first element that matches the selector, the element itself included.
+    /// Use `downcast_ref` on the node to get the element of a specific kind.
+    /// ```
+    /// # use svg_maker::{Parent, element::Element, shapes::rect::Rect};
+    /// let svg = Element::svg().push(
+    ///     Element::group()
+    ///         .id("bars")
+    ///         .push(Element::rect(0, 0, 1, 1).class("bad"))
+    ///         .push(Element::rect(0, 0, 1, 2).class("bar good")),
+    /// );
+    /// let good = svg.select("g#bars > rect.good").unwrap().unwrap();
+    /// assert!(good.downcast_ref::<Element<Rect>>().is_some());
+    /// assert_eq!(svg.select_all("svg rect:nth-child(odd)").unwrap().len(), 1);
+    /// ```
+    pub fn select(&self, selector: &str) -> Result<Option<&dyn Node>, SelectorError> {
+        let selector = Selector::parse(selector)?;
+        Ok(self.matches(&selector, true).pop().map(|(_, node)| node))
+    }
+
+    /// Returns every element that matches the selector in document order.
+    pub fn select_all(&self, selector: &str) -> Result<Vec<&dyn Node>, SelectorError> {
+        let selector = Selector::parse(selector)?;
+        Ok(self
+            .matches(&selector, false)
+            .into_iter()
+            .map(|(_, node)| node)
+            .collect())
+    }
+
+    pub fn select_mut(&mut self, selector: &str) -> Result<Option<&mut dyn Node>, SelectorError> {
+        let selector = Selector::parse(selector)?;
+        let Some((path, _)) = self.matches(&selector, true).pop() else {
+            return Ok(None);
+        };
+        Ok(Some(node_at(self, &path)))
+    }
+
+    /// Calls `f` with every element that matches the selector in document order. A matching
+    /// element can contain other matches, so they are not returned as a list like
+    /// [`Element::select_all`].
+    pub fn select_all_mut(
+        &mut self,
+        selector: &str,
+        mut f: impl FnMut(&mut dyn Node),
+    ) -> Result<(), SelectorError> {
+        let selector = Selector::parse(selector)?;
+        let paths = self
+            .matches(&selector, false)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        for path in paths {
+            f(node_at(self, &path));
+        }
+        Ok(())
+    }
+
+    /// The matching elements with the indices of the child nodes leading to them
+    fn matches(&self, selector: &Selector, first: bool) -> Vec<(Vec<usize>, &dyn Node)> {
+        let root = Sibling {
+            position: 1,
+            count: 1,
+        };
+        let mut matches = vec![];
+        collect_matches(
+            selector,
+            (self, root),
+            &mut vec![],
+            &mut vec![],
+            first,
+            &mut matches,
+        );
+        matches
+    }
+}
+
+/// Returns true when the first match is found and `first` is set
+fn collect_matches<'a>(
+    selector: &Selector,
+    node: (&'a dyn Node, Sibling),
+    ancestors: &mut Vec<(&'a dyn Node, Sibling)>,
+    path: &mut Vec<usize>,
+    first: bool,
+    matches: &mut Vec<(Vec<usize>, &'a dyn Node)>,
+) -> bool {
+    if selector.matches(node, ancestors) {
+        matches.push((path.clone(), node.0));
+        if first {
+            return true;
+        }
+    }
+    let children = node.0.child_nodes();
+    let count = children.len();
+    ancestors.push(node);
+    for (i, child) in children.into_iter().enumerate() {
+        path.push(i);
+        let sibling = Sibling {
+            position: i + 1,
+            count,
+        };
+        let found = collect_matches(selector, (child, sibling), ancestors, path, first, matches);
+        path.pop();
+        if found {
+            return true;
+        }
+    }
+    ancestors.pop();
+    false
+}
+
+fn node_at<'a>(mut node: &'a mut dyn Node, path: &[usize]) -> &'a mut dyn Node {
+    for &i in path {
+        node = node.child_nodes_mut().into_iter().nth(i).unwrap();
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::{Parent, color::Color, shapes::svg::Svg};
+
+    use super::*;
+
+    fn chart() -> Element<Svg> {
+        Element::svg()
+            .def(Element::generic("linearGradient").id("fade"))
+            .push(
+                Element::group()
+                    .id("bars")
+                    .push(Element::rect(0, 0, 1, 1).class("bar good"))
+                    .push(Element::rect(0, 0, 1, 2).class("bar"))
+                    .push(
+                        Element::rect(0, 0, 1, 3)
+                            .class("bar good")
+                            .attr("data-v", 3),
+                    )
+                    .push(Element::text(0, 0).push("bars")),
+            )
+            .push(Element::group().push(Element::rect(0, 0, 1, 4).id("legend")))
+    }
+
+    #[rstest]
+    #[case("rect", 4)]
+    #[case("*", 9)]
+    #[case("g#bars > rect.good", 2)]
+    #[case("svg > rect", 0)]
+    #[case("svg rect", 4)]
+    #[case("svg > g > rect", 4)]
+    #[case("g:nth-child(2) .bar", 3)]
+    #[case(".bar.good[data-v]", 1)]
+    #[case("[data-v='3'], #legend", 2)]
+    #[case("[data-v=4]", 0)]
+    #[case("[id=fade]", 1)]
+    #[case("rect:first-child", 2)]
+    #[case("#bars > :last-child", 1)]
+    #[case("#bars > :nth-child(2n+1)", 2)]
+    #[case("#bars > :nth-child(-n+2)", 2)]
+    #[case("#bars > :nth-child(even)", 2)]
+    #[case("#bars > :nth-child(4)", 1)]
+    #[case("svg\u{a0}rect", 4)]
+    #[case("rect[width]", 4)]
+    #[case("rect[height='2']", 1)]
+    #[case("[x]", 5)]
+    fn select_all(#[case] selector: &str, #[case] count: usize) {
+        assert_eq!(
+            chart().select_all(selector).unwrap().len(),
+            count,
+            "{selector}"
+        );
+    }
+
+    #[rstest]
+    #[case("", 0)]
+    #[case("g >", 3)]
+    #[case("g:hover", 2)]
+    #[case("rect[x", 6)]
+    #[case(":nth-child(x)", 11)]
+    #[case("g!", 1)]
+    fn invalid(#[case] selector: &str, #[case] position: usize) {
+        assert_eq!(Selector::parse(selector).unwrap_err().position, position);
+    }
+
+    #[test]
+    fn modeled_attributes() {
+        let svg = Element::svg()
+            .push(Element::circle(1, 1, 5).fill(Color::Red))
+            .push(Element::circle(1, 1, 2).attr("data-label", "a \"b\""));
+        assert_eq!(svg.select_all("circle[r=5]").unwrap().len(), 1);
+        assert_eq!(svg.select_all("[fill]").unwrap().len(), 1);
+        assert_eq!(svg.select_all("[fill=red]").unwrap().len(), 1);
+        assert_eq!(svg.select_all("[fill=blue]").unwrap().len(), 0);
+        assert_eq!(svg.select_all(r#"[data-label='a "b"']"#).unwrap().len(), 1);
+        assert_eq!(svg.select_all("[cx='1'][cy='1']").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn select_mut() {
+        let mut svg = chart();
+        assert_eq!(svg.select("rect.good").unwrap().unwrap().tag(), "rect");
+        assert!(svg.select("circle").unwrap().is_none());
+
+        svg.select_mut("#legend").unwrap().unwrap().style_mut().fill = Some(Color::Red);
+        svg.select_all_mut("g, .good", |node| {
+            node.class_mut()
+                .get_or_insert_default()
+                .push_str(" selected")
+        })
+        .unwrap();
+        assert_eq!(svg.select_all(".selected").unwrap().len(), 4);
+        let legend = svg.select("#legend").unwrap().unwrap();
+        assert!(matches!(legend.get_style().fill, Some(Color::Red)));
+    }
+}
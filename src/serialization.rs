@@ -0,0 +1,211 @@
+// ===== Serde ==================================================================
+//
+// Children are `Box<dyn ChildOf<T>>`, they are serialized as an enum tagged with the kind of the
+// child and deserialized back into a box if the parent can hold the kind, see the content model
+// in lib.rs. Character data pushed as `&'static str` is deserialized as a `String`.
+
+use std::any::Any;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser};
+
+use crate::{
+    Raw,
+    element::Element,
+    marker_traits::{ChildOf, ElementKind},
+    shapes::{
+        circle::Circle, ellipse::Ellipse, foreign_object::ForeignObject, generic::Generic,
+        group::Group, line::Line, path::Path, polygon::Polygon, polyline::Polyline, rect::Rect,
+        svg::Svg, text::Text, tspan::Tspan, use_href::Use,
+    },
+};
+
+/// Generates the tagged child enums, every element kind that can be a child is listed.
+macro_rules! children {
+    ($($kind:ident),+) => {
+        #[derive(Serialize)]
+        #[serde(rename_all = "snake_case")]
+        enum ChildRef<'a> {
+            $( $kind(&'a Element<$kind>), )+
+            CharacterData(&'a str),
+            Raw(&'a Raw),
+        }
+
+        impl<'a> ChildRef<'a> {
+            fn new(child: &'a dyn Any) -> Option<Self> {
+                $(
+                if let Some(e) = child.downcast_ref::<Element<$kind>>() {
+                    return Some(ChildRef::$kind(e));
+                }
+                )+
+                if let Some(s) = child.downcast_ref::<String>() {
+                    Some(ChildRef::CharacterData(s))
+                } else if let Some(s) = child.downcast_ref::<&'static str>() {
+                    Some(ChildRef::CharacterData(s))
+                } else {
+                    child.downcast_ref::<Raw>().map(ChildRef::Raw)
+                }
+            }
+        }
+
+        /// A deserialized child, before it is added to its parent
+        #[derive(Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        pub enum Child {
+            $( $kind(Element<$kind>), )+
+            CharacterData(String),
+            Raw(Raw),
+        }
+
+        impl Child {
+            fn name(&self) -> &'static str {
+                match self {
+                    $( Child::$kind(_) => $kind::TAG, )+
+                    Child::CharacterData(_) => "character data",
+                    Child::Raw(_) => "raw markup",
+                }
+            }
+        }
+    };
+}
+
+children!(
+    Svg,
+    Group,
+    Use,
+    Circle,
+    Ellipse,
+    Line,
+    Path,
+    Polygon,
+    Polyline,
+    Rect,
+    ForeignObject,
+    Text,
+    Tspan,
+    Generic
+);
+
+/// Element kinds that can be deserialized with their children, the children that the kind
+/// can't hold are an error. The trait is sealed, it is implemented for the kinds of the crate.
+pub trait Adopt: sealed::Adopt {}
+
+impl<T: sealed::Adopt> Adopt for T {}
+
+mod sealed {
+    use super::*;
+
+    pub trait Adopt: ElementKind + Sized + 'static {
+        /// Returns the name of the child if the kind can't hold it
+        fn adopt(child: Child) -> Result<Box<dyn ChildOf<Self>>, &'static str>;
+    }
+}
+
+macro_rules! impl_adopt {
+    ($($parent:ident: [$($child:ident),* $(; $data:ident)?]),+ $(,)?) => {
+        $(
+        impl sealed::Adopt for $parent {
+            fn adopt(child: Child) -> Result<Box<dyn ChildOf<Self>>, &'static str> {
+                #[allow(unreachable_patterns)]
+                match child {
+                    $( Child::$child(e) => Ok(Box::new(e)), )*
+                    $(
+                    Child::$data(s) => Ok(Box::new(s)),
+                    Child::Raw(raw) => Ok(Box::new(raw)),
+                    )?
+                    child => Err(child.name()),
+                }
+            }
+        }
+        )+
+    };
+}
+
+crate::content_model!(impl_adopt);
+
+/// `serde(with)` module of the children and definitions of elements
+pub(crate) mod children {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: 'static>(
+        children: &[Box<dyn ChildOf<T>>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let children = children
+            .iter()
+            .map(|c| ChildRef::new(c.as_any()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| ser::Error::custom("the kind of a child can't be serialized"))?;
+        children.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Adopt>(
+        deserializer: D,
+    ) -> Result<Vec<Box<dyn ChildOf<T>>>, D::Error> {
+        Vec::<Child>::deserialize(deserializer)?
+            .into_iter()
+            .map(|child| {
+                T::adopt(child).map_err(|name| {
+                    de::Error::custom(format!("{name} can't be a child of {}", T::TAG))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Parent,
+        color::Color,
+        element::Transform,
+        shapes::path::Command,
+        units::{Length, Percent},
+    };
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let svg = Element::svg()
+            .def(Element::generic("linearGradient").id("fade"))
+            .push(
+                Element::group()
+                    .class("bars")
+                    .transform(Transform::Translate(10., 5.))
+                    .push(Element::rect(0, 0, Percent(50), 10).fill(Color::Url("fade".into())))
+                    .push(
+                        Element::path()
+                            .move_to(0, 0)
+                            .cubic_bezier((1, 2), (3, 4), (5, 6))
+                            .push_path(Command::Raw("a 1 1 0 0 1 2 2".to_string()))
+                            .close_path()
+                            .stroke(Color::Rgb(1, 2, 3))
+                            .stroke_width(Length::from(2_u32)),
+                    ),
+            )
+            .push(
+                Element::text(0, 0)
+                    .push("static ")
+                    .push(Element::tspan(0, 0).push("a < b".to_string()))
+                    .push(Raw::new("&amp;")),
+            )
+            .push(Element::foreign_object(0, 0).push(Raw::new("<p>html</p>")));
+
+        let json = serde_json::to_string(&svg).unwrap();
+        let copy: Element<Svg> = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy.render(None), svg.render(None));
+        assert_eq!(serde_json::to_string(&copy).unwrap(), json);
+    }
+
+    #[test]
+    fn content_model() {
+        // the fields of the text kind are ignored, but the tspan can't be in a group
+        let text = Element::text(0, 0).push(Element::tspan(0, 0));
+        let json = serde_json::to_string(&text).unwrap();
+        let err = serde_json::from_str::<Element<Group>>(&json).unwrap_err();
+        assert!(
+            err.to_string().contains("tspan can't be a child of g"),
+            "{err}"
+        );
+    }
+}
@@ -2,7 +2,8 @@ use svg_maker_derive::*;
 
 use crate::{Shape, element::Element, marker_traits::ElementKind, units::Length, visit::Visit};
 
-#[derive(Debug, BaseStyle, Shape)]
+#[derive(Debug, Clone, BaseStyle, Shape)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Circle {
     cx: Length,
     cy: Length,
@@ -34,6 +35,7 @@ impl Visit for Circle {
         buffer.push_attr("cx", &self.cx);
         buffer.push_attr("cy", &self.cy);
         buffer.push_attr("r", &self.radius);
+        buffer.check_size("r", &self.radius);
     }
 }
 
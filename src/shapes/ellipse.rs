@@ -2,7 +2,8 @@ use svg_maker_derive::{BaseStyle, ClosedShape};
 
 use crate::{ElementKind, Shape, element::Element, units::Length, visit::Visit};
 
-#[derive(BaseStyle, ClosedShape, Debug)]
+#[derive(Clone, BaseStyle, ClosedShape, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ellipse {
     cx: Length,
     cy: Length,
@@ -20,6 +21,8 @@ impl Visit for Ellipse {
         buffer.push_attr("cy", &self.cy);
         buffer.push_attr("rx", &self.rx);
         buffer.push_attr("ry", &self.ry);
+        buffer.check_size("rx", &self.rx);
+        buffer.check_size("ry", &self.ry);
     }
 }
 
@@ -50,3 +53,15 @@ impl Element<Ellipse> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ellipse() {
+        let e = Element::ellipse(10, 20, 1, 1).radius_x(5).radius_y(8);
+        let expected = r#"<ellipse cx="10" cy="20" rx="5" ry="8"/>"#.to_string() + "\n";
+        assert_eq!(e.render(None), expected);
+    }
+}
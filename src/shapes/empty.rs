@@ -1,5 +1,6 @@
 use crate::{ElementKind, element::Element, visit::Visit};
 
+#[derive(Clone)]
 pub struct Empty;
 
 impl Element<Empty> {
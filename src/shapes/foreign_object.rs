@@ -1,24 +1,7 @@
-use crate::{ChildOf, ElementKind, buffer::Buffer, element::Element, units::Length, visit::Visit};
+use crate::{ElementKind, buffer::Buffer, element::Element, units::Length, visit::Visit};
 
-impl ChildOf<ForeignObject> for String {
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
-    }
-
-    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
-        self
-    }
-
-    fn get_z_index(&self) -> Option<i32> {
-        Some(0)
-    }
-
-    fn get_id(&self) -> Option<&str> {
-        None
-    }
-}
-
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForeignObject {
     x: Length,
     y: Length,
@@ -67,19 +50,32 @@ impl Visit for ForeignObject {
         buffer.push_attr("y", &self.y);
         buffer.push_attr_opt("width", &self.width);
         buffer.push_attr_opt("height", &self.height);
+        for (attr, size) in [("width", &self.width), ("height", &self.height)] {
+            if let Some(size) = size {
+                buffer.check_size(attr, size);
+            }
+        }
     }
 }
 #[cfg(test)]
 mod tests {
-    use crate::Parent;
+    use crate::{Parent, Raw};
 
     use super::*;
     #[test]
     fn foreign_object() {
         let fo = Element::foreign_object(12, 12)
-            .push("<div>asdaf</div>".to_string())
+            .push(Raw::new("<div>asdaf</div>"))
+            .render(None);
+        let expected = "<foreignObject x=\"12\" y=\"12\">\n<div>asdaf</div></foreignObject>\n";
+        assert_eq!(fo, expected);
+
+        // strings are text, not markup
+        let fo = Element::foreign_object(12, 12)
+            .push("<div>asdaf</div>")
             .render(None);
-        let expected = r#"<foreignObject x="0" y="0" width="0" height="0"/>"#.to_string() + "\n";
-        assert_eq!(fo, expected)
+        let expected =
+            "<foreignObject x=\"12\" y=\"12\">\n&lt;div&gt;asdaf&lt;/div&gt;</foreignObject>\n";
+        assert_eq!(fo, expected);
     }
 }
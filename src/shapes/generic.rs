@@ -0,0 +1,104 @@
+use svg_maker_derive::BaseStyle;
+
+use crate::{
+    buffer::{Buffer, is_xml_name},
+    element::Element,
+    marker_traits::ElementKind,
+    visit::Visit,
+};
+
+/// An element that the crate doesn't model, like `<linearGradient>` or `<title>`.
+/// The tag and attributes are rendered as is and it can hold any element as a child.
+#[derive(Debug, Clone, Default, BaseStyle)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Generic {
+    tag: String,
+    attrs: Vec<(String, String)>,
+}
+
+impl Generic {
+    pub(crate) fn new(tag: &str, attrs: Vec<(String, String)>) -> Self {
+        Self {
+            tag: tag.to_string(),
+            attrs,
+        }
+    }
+}
+
+impl Element<Generic> {
+    /// Creates an element with any tag, the attributes are set with [`Element::attr`].
+    /// ```
+    /// # use svg_maker::{Parent, element::Element};
+    /// let gradient = Element::generic("linearGradient")
+    ///     .id("fade")
+    ///     .push(Element::generic("stop").attr("offset", 0).attr("stop-color", "white"))
+    ///     .push(Element::generic("stop").attr("offset", 1).attr("stop-color", "black"));
+    /// assert!(gradient.render(None).contains(r#"<stop offset="1" stop-color="black"/>"#));
+    /// ```
+    ///
+    /// # Panics
+    /// If the tag is not a valid xml name, ie `g onload=alert(1)`.
+    pub fn generic(tag: &str) -> Self {
+        assert!(is_xml_name(tag), "invalid tag name: {tag:?}");
+        Element::new(Generic::new(tag, vec![]))
+    }
+}
+
+impl ElementKind for Generic {
+    // the tag is only known at runtime, see `tag()`
+    const TAG: &'static str = "GENERIC";
+
+    fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    fn attrs(&self) -> Option<&[(String, String)]> {
+        Some(&self.attrs)
+    }
+
+    fn attrs_mut(&mut self) -> Option<&mut Vec<(String, String)>> {
+        Some(&mut self.attrs)
+    }
+}
+
+impl Visit for Generic {
+    fn visit(&self, buffer: &mut Buffer) {
+        for (name, value) in &self.attrs {
+            buffer.push_extra_attr(name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Options, Parent};
+
+    use super::*;
+
+    #[test]
+    fn generic() {
+        let stop = |offset: &str| Element::generic("stop").attr("offset", offset);
+        let gradient = Element::generic("linearGradient")
+            .id("fade")
+            .push(stop("0"))
+            .push(stop("1"));
+        assert_eq!(gradient.push(stop("1")).len(), 3);
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        let title = Element::generic("title").push("Revenue");
+        assert_eq!(title.render(Some(opts)), "<title>Revenue</title>");
+
+        let stop = stop("0").attr("offset", "0.5");
+        assert_eq!(stop.get_attr("offset"), Some("0.5"));
+        assert_eq!(stop.kind.attrs.len(), 1);
+        assert_eq!(stop.render(None), "<stop offset=\"0.5\"/>\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid tag name")]
+    fn invalid_tag() {
+        Element::generic("g onload=alert(1)");
+    }
+}
@@ -2,7 +2,8 @@ use svg_maker_derive::BaseStyle;
 
 use crate::{element::Element, marker_traits::ElementKind, visit::Visit};
 
-#[derive(Debug, Default, BaseStyle)]
+#[derive(Debug, Clone, Default, BaseStyle)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Group {}
 
 impl Element<Group> {
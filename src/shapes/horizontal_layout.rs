@@ -1,3 +1,4 @@
+#![allow(dead_code)]
 struct HorizontalLayout {
     padding: u32,
 }
@@ -2,7 +2,8 @@ use crate::{BaseStyle, ElementKind, Hx, OpenEndedShape};
 
 use crate::{buffer::Buffer, element::Element, units::Length, visit::Visit};
 
-#[derive(Debug, Default, BaseStyle, OpenEndedShape, Hx)]
+#[derive(Debug, Clone, Default, BaseStyle, OpenEndedShape, Hx)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line {
     x1: Length,
     y1: Length,
@@ -44,10 +45,20 @@ impl ElementKind for Line {
 }
 impl Visit for Line {
     fn visit(&self, buffer: &mut Buffer) {
-        buffer.push_tag("line");
         buffer.push_attr("x1", &self.x1);
         buffer.push_attr("y1", &self.y1);
         buffer.push_attr("x2", &self.x2);
         buffer.push_attr("y2", &self.y2);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line() {
+        let line = Element::line(1, 2, 3, 4).render(None);
+        assert_eq!(line, "<line x1=\"1\" y1=\"2\" x2=\"3\" y2=\"4\"/>\n");
+    }
+}
@@ -2,6 +2,7 @@ pub mod circle;
 pub mod ellipse;
 pub mod empty;
 pub mod foreign_object;
+pub mod generic;
 pub mod group;
 mod horizontal_layout;
 pub mod line;
@@ -1,15 +1,17 @@
 use crate::{
     buffer::Buffer,
+    diagnostics::{Code, Severity},
     element::Element,
     marker_traits::ElementKind,
     measure::{self, Measurement},
     path_parser,
-    units::{Coord, CubicArgs, XCoord, YCoord},
+    units::{ArcArgs, Coord, CubicArgs, QuadraticArgs, SmoothCubicArgs, XCoord, YCoord},
     visit::Visit,
 };
 use svg_maker_derive::*;
 
-#[derive(Debug, BaseStyle, Shape)]
+#[derive(Debug, Clone, BaseStyle, Shape)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Path {
     pub path: Vec<Command>,
 }
@@ -54,14 +56,14 @@ impl Path {
                 Command::HorizontalLineRelative(xcoord) => (xcoord.0, 0., true),
                 Command::CubicBezier(args) => (args.end.0.0, args.end.1.0, false),
                 Command::CubicBezierRelative(args) => (args.end.0.0, args.end.1.0, true),
-                Command::CubicBezierExtended => todo!(),
-                Command::CubicBezierExtendedRelaitve => todo!(),
-                Command::QuadraticBezier => todo!(),
-                Command::QuadraticBezierRelative => todo!(),
-                Command::QuadraticBezierExtended => todo!(),
-                Command::QuadraticBezierExtendedRelaitve => todo!(),
-                Command::Arc => todo!(),
-                Command::ArcRelative => todo!(),
+                Command::CubicBezierExtended(args) => (args.end.0.0, args.end.1.0, false),
+                Command::CubicBezierExtendedRelative(args) => (args.end.0.0, args.end.1.0, true),
+                Command::QuadraticBezier(args) => (args.end.0.0, args.end.1.0, false),
+                Command::QuadraticBezierRelative(args) => (args.end.0.0, args.end.1.0, true),
+                Command::QuadraticBezierExtended(end) => (end.0.0, end.1.0, false),
+                Command::QuadraticBezierExtendedRelative(end) => (end.0.0, end.1.0, true),
+                Command::Arc(args) => (args.end.0.0, args.end.1.0, false),
+                Command::ArcRelative(args) => (args.end.0.0, args.end.1.0, true),
                 Command::Raw(_) => todo!(),
                 Command::ClosePath => todo!(),
                 Command::Invalid => todo!(),
@@ -88,7 +90,7 @@ impl Element<Path> {
     }
 
     /// appends a path or sub path to the path
-    pub fn path_from_str(mut self, path: &str, scale: f64) -> Self {
+    pub fn path_from_str(self, path: &str, _scale: f64) -> Self {
         // TODO:
         // 1. parse to commands
         // 2. scale to appropriate scale
@@ -178,6 +180,38 @@ impl Element<Path> {
         self
     }
 
+    /// Creates a quadratic bezier curve.
+    /// `(f64, f64)` (x, y) implements `Into<Coord>`
+    pub fn quadratic_bezier<P>(mut self, p1: P, end: P) -> Self
+    where
+        P: Into<Coord>,
+    {
+        self.path.push(Command::QuadraticBezier(QuadraticArgs {
+            p1: p1.into(),
+            end: end.into(),
+        }));
+        self
+    }
+
+    /// Creates a quadratic bezier curve where all the values are relative to current pos.
+    /// `(f64, f64)` (x, y) implements `Into<Coord>`
+    pub fn quadratic_bezier_relative<P>(mut self, p1: P, end: P) -> Self
+    where
+        P: Into<Coord>,
+    {
+        self.path
+            .push(Command::QuadraticBezierRelative(QuadraticArgs {
+                p1: p1.into(),
+                end: end.into(),
+            }));
+        self
+    }
+
+    pub fn close_path(mut self) -> Self {
+        self.path.push(Command::ClosePath);
+        self
+    }
+
     /// Creates a cubic bezier curve where all the values are relative to current pos.
     /// `(f64, f64)` (x, y) implements `Into<Coord>`
     pub fn cubic_bezier_relative<P>(mut self, p1: P, p2: P, end: P) -> Self
@@ -205,16 +239,22 @@ impl ElementKind for Path {
 
 impl Visit for Path {
     fn visit(&self, buffer: &mut Buffer) {
-        if let Some(&Command::MoveTo(_)) = self.path.first() {
-            buffer
-                .warnings
-                .push("Path must start with a MoveTo command".to_string());
+        if !matches!(
+            self.path.first(),
+            None | Some(Command::MoveTo(_) | Command::MoveToRelative(_) | Command::Raw(_))
+        ) {
+            buffer.diagnostic(
+                Severity::Error,
+                Code::PathWithoutMoveTo,
+                format_args!("path must start with a MoveTo command"),
+            );
         }
         buffer.push_attr("d", &self.path);
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Command {
     MoveTo(Coord),
     MoveToRelative(Coord),
@@ -228,22 +268,22 @@ pub enum Command {
     //c dx1 dy1 dx2 dy2 dx dy
     CubicBezier(CubicArgs),
     CubicBezierRelative(CubicArgs),
-    //S x y
-    //s dx dy
-    CubicBezierExtended,
-    CubicBezierExtendedRelaitve,
+    //S x2 y2 x y
+    //s dx2 dy2 dx dy
+    CubicBezierExtended(SmoothCubicArgs),
+    CubicBezierExtendedRelative(SmoothCubicArgs),
     //Q x1 y1 x y
     //q dx1 dy1 dx dy
-    QuadraticBezier,
-    QuadraticBezierRelative,
+    QuadraticBezier(QuadraticArgs),
+    QuadraticBezierRelative(QuadraticArgs),
     //T x y
     //t dx dy
-    QuadraticBezierExtended,
-    QuadraticBezierExtendedRelaitve,
+    QuadraticBezierExtended(Coord),
+    QuadraticBezierExtendedRelative(Coord),
     //  A rx ry x-axis-rotation large-arc-flag sweep-flag x y
     // a rx ry x-axis-rotation large-arc-flag sweep-flag dx dy
-    Arc,
-    ArcRelative,
+    Arc(ArcArgs),
+    ArcRelative(ArcArgs),
     Raw(String),
     ClosePath,
     Invalid,
@@ -262,6 +302,14 @@ impl Visit for Command {
             Command::HorizontalLineRelative(dx) => dx.visit_prefix(buffer, "h"),
             Command::CubicBezier(args) => args.visit_prefix(buffer, "C"),
             Command::CubicBezierRelative(args) => args.visit_prefix(buffer, "c"),
+            Command::QuadraticBezier(args) => args.visit_prefix(buffer, "Q"),
+            Command::QuadraticBezierRelative(args) => args.visit_prefix(buffer, "q"),
+            Command::CubicBezierExtended(args) => args.visit_prefix(buffer, "S"),
+            Command::CubicBezierExtendedRelative(args) => args.visit_prefix(buffer, "s"),
+            Command::QuadraticBezierExtended(end) => end.visit_prefix(buffer, "T"),
+            Command::QuadraticBezierExtendedRelative(end) => end.visit_prefix(buffer, "t"),
+            Command::Arc(args) => args.visit_prefix(buffer, "A"),
+            Command::ArcRelative(args) => args.visit_prefix(buffer, "a"),
             // TODO: check the end of s and add a space.
             Command::Raw(s) => buffer.push_str(s),
             Command::ClosePath => buffer.push_str("Z"),
@@ -286,6 +334,7 @@ mod tests {
             .cubic_bezier((9, 9), (1, 1), (2, 3))
             .horizontal_line_relative(100);
 
-        assert_eq!(e.render(Some(Options::default())), "2");
+        let expected = r#"<path d="M10,10 h10 l20,90 h-10 C9,9 1,1 2,3 h100"/>"#.to_string() + "\n";
+        assert_eq!(e.render(Some(Options::default())), expected);
     }
 }
@@ -4,7 +4,8 @@ use crate::{
     buffer::Buffer, element::Element, marker_traits::ElementKind, units::Coord, visit::Visit,
 };
 
-#[derive(Debug, BaseStyle, ClosedShape)]
+#[derive(Debug, Clone, BaseStyle, ClosedShape)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Polygon {
     points: Vec<Coord>,
 }
@@ -4,7 +4,8 @@ use crate::{
     buffer::Buffer, element::Element, marker_traits::ElementKind, units::Coord, visit::Visit,
 };
 
-#[derive(Debug, BaseStyle, OpenEndedShape)]
+#[derive(Debug, Clone, BaseStyle, OpenEndedShape)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Polyline {
     points: Vec<Coord>,
 }
@@ -43,3 +44,17 @@ impl Visit for Polyline {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polyline() {
+        let polyline = Element::polyline()
+            .add_points(vec![(0, 0), (10, 10)])
+            .add_point((20, 0));
+        let expected = r#"<polyline points="0,0 10,10 20,0"/>"#.to_string() + "\n";
+        assert_eq!(polyline.render(None), expected);
+    }
+}
@@ -4,7 +4,8 @@ use crate::{
     buffer::Buffer, element::Element, marker_traits::ElementKind, units::Length, visit::Visit,
 };
 
-#[derive(Debug, Default, BaseStyle, ClosedShape)]
+#[derive(Debug, Clone, Default, BaseStyle, ClosedShape)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     x: Length,
     y: Length,
@@ -61,6 +62,8 @@ impl Visit for Rect {
         buffer.push_attr("height", &self.height);
         buffer.push_attr_opt("rx", &self.rx);
         buffer.push_attr_opt("ry", &self.ry);
+        buffer.check_size("width", &self.width);
+        buffer.check_size("height", &self.height);
     }
 }
 
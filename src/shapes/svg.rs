@@ -1,26 +1,39 @@
-use std::{error::Error, fmt::Debug, fs::File, io::Write};
+use std::{
+    error::Error,
+    fmt::Debug,
+    fs::File,
+    io::{BufWriter, Write},
+};
 
 use svg_maker_derive::*;
 
 use crate::{
     PreserveAspectRatio, Viewbox,
     buffer::Buffer,
+    diagnostics::url_references,
     element::Element,
     marker_traits::{BaseElement, ChildOf, ElementKind},
+    node::Node,
     units::{AlignAspectRatio, Length, MeetOrSlice},
     visit::Visit,
 };
 
-#[derive(BaseStyle, ContainerElement, Debug)]
+#[derive(Clone, BaseStyle, ContainerElement, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Svg {
-    w: Option<Length>,
-    h: Option<Length>,
-    viewbox: Option<Viewbox>,
+    pub(crate) w: Option<Length>,
+    pub(crate) h: Option<Length>,
+    pub(crate) viewbox: Option<Viewbox>,
     version: String,
     preserve_aspect_ratio: Option<PreserveAspectRatio>,
     namespace: String,
-    css: Option<String>,
-    defs: Vec<Box<dyn ChildOf<Self>>>,
+    pub(crate) css: Option<String>,
+    /// @font-face rules of embedded fonts, rendered before the css
+    pub(crate) font_faces: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::children"))]
+    pub(crate) defs: Vec<Box<dyn ChildOf<Self>>>,
+    /// last number used by `Element::auto_id`
+    pub(crate) next_id: u32,
 }
 //
 // impl<T: ElementKind + Visit + 'static + Debug> ChildOf<Svg> for Element<T> {}
@@ -111,19 +124,11 @@ impl Element<Svg> {
         Element<E>: ChildOf<Svg> + BaseElement,
         E: ElementKind + 'static,
     {
-        debug_assert!(
-            el.get_id().is_some(),
-            "a definition is useless without an id"
-        );
         self.defs.push(Box::new(el));
         self
     }
 
     pub fn defs(mut self, elements: Vec<Box<dyn ChildOf<Svg>>>) -> Self {
-        // debug_assert!(
-        //     elements.iter().all(|e| e.get_id().is_some()),
-        //     "a element definition is useless without an id"
-        // );
         for element in elements {
             self.defs.push(element);
         }
@@ -131,8 +136,9 @@ impl Element<Svg> {
     }
 
     pub fn render_to_file(&self, path: &str) -> Result<(), Box<dyn Error>> {
-        let mut f = File::create(path)?;
-        f.write_all(self.render(None).as_bytes())?;
+        let mut f = BufWriter::new(File::create(path)?);
+        self.render_to(&mut f, None)?;
+        f.flush()?;
         Ok(())
     }
 
@@ -203,6 +209,60 @@ impl Element<Svg> {
 
 impl ElementKind for Svg {
     const TAG: &'static str = "svg";
+
+    fn nodes(&self) -> Vec<&dyn Node> {
+        self.defs.iter().filter_map(|d| d.as_node()).collect()
+    }
+
+    fn nodes_mut(&mut self) -> Vec<&mut dyn Node> {
+        self.defs
+            .iter_mut()
+            .filter_map(|d| d.as_node_mut())
+            .collect()
+    }
+
+    fn has_content(&self) -> bool {
+        self.css.is_some() || !self.font_faces.is_empty() || !self.defs.is_empty()
+    }
+
+    fn visit_content(&self, buffer: &mut Buffer) {
+        if self.css.is_some() || !self.font_faces.is_empty() {
+            buffer.enter("style", None);
+            buffer.push_tag("style");
+            buffer.push_tag_end();
+            for font_face in &self.font_faces {
+                buffer.push_text(font_face);
+                buffer.push('\n');
+            }
+            if let Some(css) = &self.css {
+                // rules like `rect { fill: url(#fade) }` use the definitions too
+                for target in url_references(css) {
+                    buffer.reference(target);
+                }
+                buffer.push_text(css);
+            }
+            buffer.push_tag_close("style");
+            buffer.leave();
+        }
+        let defs = self
+            .defs
+            .iter()
+            .filter(|def| match &buffer.used_ids {
+                Some(used) => def.get_id().is_some_and(|id| used.contains(id)),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        if !defs.is_empty() {
+            buffer.enter("defs", None);
+            buffer.push_tag("defs");
+            buffer.push_tag_end();
+            for def in defs {
+                def.visit(buffer);
+            }
+            buffer.push_tag_close("defs");
+            buffer.leave();
+        }
+    }
 }
 
 impl Visit for Svg {
@@ -210,7 +270,12 @@ impl Visit for Svg {
         buffer.opts.optimizations.remove_unit_for_px = true;
         buffer.push_attr_opt("width", &self.w);
         buffer.push_attr_opt("height", &self.h);
-        buffer.push_attr_opt("viewbox", &self.viewbox);
+        for (attr, size) in [("width", &self.w), ("height", &self.h)] {
+            if let Some(size) = size {
+                buffer.check_size(attr, size);
+            }
+        }
+        buffer.push_attr_opt("viewBox", &self.viewbox);
         if let Some(PreserveAspectRatio { alignment, .. }) = &self.preserve_aspect_ratio
             && *alignment != AlignAspectRatio::None
         {
@@ -218,13 +283,6 @@ impl Visit for Svg {
         }
         buffer.push_attr("version", &self.version);
         buffer.push_attr("xmlns", &self.namespace);
-        buffer.push_tag_end();
-        if let Some(css) = &self.css {
-            buffer.push_tag("style");
-            buffer.push_tag_end();
-            buffer.push_str(css);
-            buffer.push_tag_close("style");
-        }
     }
 }
 
@@ -257,7 +315,9 @@ impl Default for Svg {
             }),
             preserve_aspect_ratio: None,
             css: None,
+            font_faces: Vec::new(),
             defs: Vec::new(),
+            next_id: 0,
         }
     }
 }
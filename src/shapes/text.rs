@@ -7,9 +7,9 @@
 // Text content child elements
 // <a>
 
-use std::fmt::Debug;
+use std::{any::Any, fmt::Debug};
 
-use svg_maker_derive::BaseStyle;
+use svg_maker_derive::{BaseStyle, TextElement};
 
 // text can be child of
 //<svg> <g> <a> <defs> <marker> <mask> <pattern> <symbol> <switch> <clipPath>
@@ -18,61 +18,28 @@ use svg_maker_derive::BaseStyle;
 use crate::{
     buffer::Buffer,
     element::Element,
-    marker_traits::{ChildOf, ElementKind},
+    font::Font,
+    marker_traits::ElementKind,
+    measure::Measurement,
+    shapes::tspan::Tspan,
     units::{Length, TextAnchor},
     visit::Visit,
 };
 
-//need to implement this manually since String is not an Element<T>
-impl ChildOf<Text> for String {
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
-    }
-
-    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
-        self
-    }
-
-    fn get_z_index(&self) -> Option<i32> {
-        None
-    }
-
-    fn get_id(&self) -> Option<&str> {
-        None
-    }
-}
-impl ChildOf<Text> for &'static str {
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
-    }
-
-    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
-        self
-    }
-
-    fn get_z_index(&self) -> Option<i32> {
-        None
-    }
-
-    fn get_id(&self) -> Option<&str> {
-        None
-    }
-}
-
-#[derive(Debug, Default, BaseStyle)]
+#[derive(Debug, Clone, Default, BaseStyle, TextElement)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Text {
-    x: Vec<Length>,
-    y: Vec<Length>,
-    dx: Vec<Length>,
-    dy: Vec<Length>,
-    rotate: Option<Vec<f64>>,
+    pub(crate) x: Vec<Length>,
+    pub(crate) y: Vec<Length>,
+    pub(crate) dx: Vec<Length>,
+    pub(crate) dy: Vec<Length>,
+    pub(crate) rotate: Option<Vec<f64>>,
     length_adjust: Option<LengthAdjust>,
     text_length: Option<Length>,
     text_anchor: Option<TextAnchor>,
 }
 
 impl Element<Text> {
-    // TODO: a constructor for each xy, xy: lenght, xy: Vec<lenght>, dxy: length, dxy : vec<lenght>
     pub fn text<X, Y>(x: X, y: Y) -> Self
     where
         X: Into<Length>,
@@ -86,12 +53,48 @@ impl Element<Text> {
         Element::new(text)
     }
 
+    /// Creates a text element with per glyph positioning, the n:th value positions the n:th
+    /// glyph, the last value applies to the rest of the glyphs.
+    /// ```
+    /// # use svg_maker::element::Element;
+    /// # use svg_maker::Parent;
+    /// let text = Element::text_positioned([10, 20, 30], [5]).push("abc");
+    /// assert!(text.render(None).contains(r#"x="10 20 30" y="5""#));
+    /// ```
+    pub fn text_positioned<X, Y>(
+        x: impl IntoIterator<Item = X>,
+        y: impl IntoIterator<Item = Y>,
+    ) -> Self
+    where
+        X: Into<Length>,
+        Y: Into<Length>,
+    {
+        let text = Text {
+            x: x.into_iter().map(Into::into).collect(),
+            y: y.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        };
+        Element::new(text)
+    }
+
+    /// Shifts the glyphs along the x axis relative to the previous glyph
+    pub fn dx<L: Into<Length>>(mut self, dx: impl IntoIterator<Item = L>) -> Self {
+        self.dx = dx.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Shifts the glyphs along the y axis relative to the previous glyph
+    pub fn dy<L: Into<Length>>(mut self, dy: impl IntoIterator<Item = L>) -> Self {
+        self.dy = dy.into_iter().map(Into::into).collect();
+        self
+    }
+
     pub fn rotate(mut self, angle: Vec<f64>) -> Self {
         self.rotate = Some(angle);
         self
     }
 
-    pub fn lengt_adjust(mut self, value: LengthAdjust) -> Self {
+    pub fn length_adjust(mut self, value: LengthAdjust) -> Self {
         self.length_adjust = Some(value);
         self
     }
@@ -109,10 +112,53 @@ impl Element<Text> {
     }
 }
 
+impl Element<Text> {
+    /// Measures the character data of the text and its tspans with the font.
+    /// A tspan that sets x starts a new line.
+    pub fn measure(&self, font: &Font) -> Measurement {
+        font.measure(&self.text_lines().join("\n"))
+    }
+
+    /// The character data of the text and its tspans, split into lines.
+    pub(crate) fn text_lines(&self) -> Vec<String> {
+        let mut lines = vec![String::new()];
+        for child in self.children() {
+            collect_text(child.as_any(), &mut lines);
+        }
+        lines
+    }
+}
+
+impl Text {
+    pub(crate) fn anchor(&self) -> TextAnchor {
+        self.text_anchor.unwrap_or(TextAnchor::Start)
+    }
+}
+
+fn collect_text(child: &dyn Any, lines: &mut Vec<String>) {
+    // safe to unwrap, there is always at least one line
+    if let Some(s) = child.downcast_ref::<String>() {
+        lines.last_mut().unwrap().push_str(s);
+    } else if let Some(s) = child.downcast_ref::<&'static str>() {
+        lines.last_mut().unwrap().push_str(s);
+    } else if let Some(tspan) = child.downcast_ref::<Element<Tspan>>() {
+        if tspan.starts_line() && !lines.last().unwrap().is_empty() {
+            lines.push(String::new());
+        }
+        for child in tspan.children() {
+            collect_text(child.as_any(), lines);
+        }
+    }
+}
+
 impl Visit for Text {
     fn visit(&self, buffer: &mut Buffer) {
-        buffer.push_attr("x", &self.x);
-        buffer.push_attr("y", &self.y);
+        if !self.x.is_empty() {
+            buffer.push_attr("x", &self.x);
+        }
+        if !self.y.is_empty() {
+            buffer.push_attr("y", &self.y);
+        }
         if !self.dx.is_empty() {
             buffer.push_attr("dx", &self.dx);
         }
@@ -121,8 +167,8 @@ impl Visit for Text {
         }
 
         buffer.push_attr_opt("rotate", &self.rotate);
-        buffer.push_attr_opt("lenghtAdjust", &self.length_adjust);
-        buffer.push_attr_opt("textLenght", &self.text_length);
+        buffer.push_attr_opt("lengthAdjust", &self.length_adjust);
+        buffer.push_attr_opt("textLength", &self.text_length);
         buffer.push_attr_opt("text-anchor", &self.text_anchor);
     }
 }
@@ -131,7 +177,8 @@ impl ElementKind for Text {
     const TAG: &'static str = "text";
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LengthAdjust {
     #[default]
     Spacing,
@@ -150,7 +197,11 @@ impl Visit for LengthAdjust {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Options, Parent};
+    use crate::{
+        Options, Parent,
+        style::{FontWeight, TextDecoration},
+        units::Px,
+    };
 
     use super::*;
 
@@ -166,4 +217,56 @@ mod tests {
         let rendered = text.render(Some(opts));
         assert_eq!(rendered, expected);
     }
+
+    #[test]
+    fn escaping() {
+        let text = Element::text(1, 2)
+            .id(r#"a"b"#)
+            .class("x&y")
+            .font_family(r#""Fira Sans", serif"#)
+            .push("1 < 2 & 3")
+            .push(Element::tspan(0, 0).push("</text>"));
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+
+        let expected = r#"<text id="a&quot;b" class="x&amp;y" x="1" y="2" style="font-family: &quot;Fira Sans&quot;, serif;">1 &lt; 2 &amp; 3<tspan x="0" y="0">&lt;/text&gt;</tspan></text>"#;
+        assert_eq!(text.render(Some(opts)), expected);
+    }
+
+    #[test]
+    fn measure() {
+        let font = Font::new("Courier", 10.);
+        let text = Element::text(0, 0)
+            .push("ab")
+            .push(Element::tspan(0, 10).push("cdef"))
+            .push(Element::<Tspan>::tspan_positioned(Vec::<Length>::new(), [20]).push("g"));
+        let m = text.measure(&font);
+        // "ab" and "cdefg" on two lines, courier is 0.6em wide
+        assert!((m.width() - 30.).abs() < 1e-9);
+        assert!((m.height() - font.measure("ab\ncdefg").height()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn text_layout_attributes() {
+        let text = Element::text_positioned([1, 2], [3])
+            .dx([Px(1.), Px(2.)])
+            .dy([4])
+            .length_adjust(LengthAdjust::SpacingAndGlyphs)
+            .text_length(40)
+            .font_family("Inter, sans-serif")
+            .font_size(12)
+            .font_weight(FontWeight::Weight(600))
+            .letter_spacing(1)
+            .text_decoration(TextDecoration::Underline)
+            .push("ab");
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+
+        let expected = r#"<text x="1 2" y="3" dx="1 2" dy="4" lengthAdjust="spacingAndGlyphs" textLength="40" style="font-family: Inter, sans-serif; font-size: 12px; font-weight: 600; letter-spacing: 1px; text-decoration: underline;">ab</text>"#;
+        assert_eq!(text.render(Some(opts)), expected);
+    }
 }
@@ -1,42 +1,25 @@
-use std::fmt::Debug;
+use svg_maker_derive::{BaseStyle, TextElement};
 
 use crate::{
     buffer::Buffer,
     element::Element,
-    marker_traits::{ChildOf, ElementKind},
+    marker_traits::ElementKind,
     shapes::text::LengthAdjust,
-    units::Length,
+    units::{Length, TextAnchor},
     visit::Visit,
 };
 
-//need to implement this manually since String is not an Element<T>
-impl ChildOf<Tspan> for String {
-    fn as_any(&self) -> &dyn std::any::Any {
-        todo!()
-    }
-
-    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
-        todo!()
-    }
-
-    fn get_z_index(&self) -> Option<i32> {
-        None
-    }
-
-    fn get_id(&self) -> Option<&str> {
-        None
-    }
-}
-
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, BaseStyle, TextElement)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tspan {
-    x: Vec<Length>,
-    y: Vec<Length>,
-    dx: Vec<Length>,
-    dy: Vec<Length>,
-    rotate: Option<Vec<f64>>,
+    pub(crate) x: Vec<Length>,
+    pub(crate) y: Vec<Length>,
+    pub(crate) dx: Vec<Length>,
+    pub(crate) dy: Vec<Length>,
+    pub(crate) rotate: Option<Vec<f64>>,
     length_adjust: Option<LengthAdjust>,
     text_length: Option<Length>,
+    pub(crate) text_anchor: Option<TextAnchor>,
 }
 
 impl Element<Tspan> {
@@ -53,12 +36,42 @@ impl Element<Tspan> {
         Element::new(tspan)
     }
 
+    /// Creates a tspan with per glyph positioning, see [`Element::text_positioned`].
+    /// Empty iterators leaves the position to be inherited from the text flow.
+    pub fn tspan_positioned<X, Y>(
+        x: impl IntoIterator<Item = X>,
+        y: impl IntoIterator<Item = Y>,
+    ) -> Self
+    where
+        X: Into<Length>,
+        Y: Into<Length>,
+    {
+        let tspan = Tspan {
+            x: x.into_iter().map(Into::into).collect(),
+            y: y.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        };
+        Element::new(tspan)
+    }
+
+    /// Shifts the glyphs along the x axis relative to the previous glyph
+    pub fn dx<L: Into<Length>>(mut self, dx: impl IntoIterator<Item = L>) -> Self {
+        self.dx = dx.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Shifts the glyphs along the y axis relative to the previous glyph
+    pub fn dy<L: Into<Length>>(mut self, dy: impl IntoIterator<Item = L>) -> Self {
+        self.dy = dy.into_iter().map(Into::into).collect();
+        self
+    }
+
     pub fn rotate(mut self, angle: Vec<f64>) -> Self {
         self.rotate = Some(angle);
         self
     }
 
-    pub fn lengt_adjust(mut self, value: LengthAdjust) -> Self {
+    pub fn length_adjust(mut self, value: LengthAdjust) -> Self {
         self.length_adjust = Some(value);
         self
     }
@@ -67,12 +80,28 @@ impl Element<Tspan> {
         self.text_length = Some(value.into());
         self
     }
+
+    pub fn text_anchor(mut self, value: TextAnchor) -> Self {
+        self.text_anchor = Some(value);
+        self
+    }
+}
+
+impl Tspan {
+    /// a tspan that sets x starts a new line in the text flow
+    pub(crate) fn starts_line(&self) -> bool {
+        !self.x.is_empty()
+    }
 }
 
 impl Visit for Tspan {
     fn visit(&self, buffer: &mut Buffer) {
-        buffer.push_attr("x", &self.x);
-        buffer.push_attr("y", &self.y);
+        if !self.x.is_empty() {
+            buffer.push_attr("x", &self.x);
+        }
+        if !self.y.is_empty() {
+            buffer.push_attr("y", &self.y);
+        }
         if !self.dx.is_empty() {
             buffer.push_attr("dx", &self.dx);
         }
@@ -81,8 +110,9 @@ impl Visit for Tspan {
         }
 
         buffer.push_attr_opt("rotate", &self.rotate);
-        buffer.push_attr_opt("lenghtAdjust", &self.length_adjust);
-        buffer.push_attr_opt("textLenght", &self.text_length);
+        buffer.push_attr_opt("lengthAdjust", &self.length_adjust);
+        buffer.push_attr_opt("textLength", &self.text_length);
+        buffer.push_attr_opt("text-anchor", &self.text_anchor);
     }
 }
 
@@ -92,7 +122,11 @@ impl ElementKind for Tspan {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Options, Parent};
+    use crate::{
+        Options, Parent,
+        style::BaselineShift,
+        units::{Px, TextAnchor},
+    };
 
     use super::*;
 
@@ -106,4 +140,19 @@ mod tests {
         let rendered = _t.render(Some(opts));
         assert_eq!(rendered, expected);
     }
+
+    #[test]
+    fn tspan_flow() {
+        let t = Element::<Tspan>::tspan_positioned([0], Vec::<Length>::new())
+            .dy([Px(16.)])
+            .text_anchor(TextAnchor::End)
+            .baseline_shift(BaselineShift::Super)
+            .push("line");
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        let expected =
+            r#"<tspan x="0" dy="16" text-anchor="end" style="baseline-shift: super;">line</tspan>"#;
+        assert_eq!(t.render(Some(opts)), expected);
+    }
 }
@@ -2,18 +2,20 @@ use svg_maker_derive::{BaseStyle, Hx, Shape};
 
 use crate::{
     buffer::Buffer,
+    diagnostics::{Code, Severity},
     element::Element,
     marker_traits::ElementKind,
     units::{Length, XCoord, YCoord},
     visit::Visit,
 };
 
-#[derive(Debug, Default, Hx, Shape, BaseStyle)]
+#[derive(Debug, Clone, Default, Hx, Shape, BaseStyle)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Use {
     x: XCoord,
     y: YCoord,
-    width: Length,
-    height: Length,
+    width: Option<Length>,
+    height: Option<Length>,
     href: String,
 }
 
@@ -62,33 +64,61 @@ impl Element<Use> {
 
     #[must_use]
     pub fn height<H: Into<Length>>(mut self, height: H) -> Self {
-        self.height = height.into();
+        self.height = Some(height.into());
         self
     }
 
     #[must_use]
     pub fn width<H: Into<Length>>(mut self, width: H) -> Self {
-        self.width = width.into();
+        self.width = Some(width.into());
         self
     }
 }
 
 impl ElementKind for Use {
     const TAG: &'static str = "use";
+
+    fn href_mut(&mut self) -> Option<&mut String> {
+        Some(&mut self.href)
+    }
 }
 
 impl Visit for Use {
     fn visit(&self, buffer: &mut Buffer) {
-        debug_assert!(!self.href.is_empty(), "Use without a href is useless");
-
         if self.href.is_empty() {
+            buffer.diagnostic(
+                Severity::Warning,
+                Code::UseWithoutHref,
+                format_args!("use without a href renders nothing"),
+            );
             return;
         }
-        buffer.push_tag("Use");
+        if self.href.starts_with('#') {
+            buffer.reference(&self.href);
+        }
         buffer.push_attr("x", &self.x);
         buffer.push_attr("y", &self.y);
-        buffer.push_attr("width", &self.width);
-        buffer.push_attr("height", &self.height);
+        buffer.push_attr_opt("width", &self.width);
+        buffer.push_attr_opt("height", &self.height);
         buffer.push_attr("href", &self.href);
+        for (attr, size) in [("width", &self.width), ("height", &self.height)] {
+            if let Some(size) = size {
+                buffer.check_size(attr, size);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn use_href() {
+        let u = Element::use_href(1, 2).href("icon").render(None);
+        assert_eq!(u, "<use x=\"1\" y=\"2\" href=\"#icon\"/>\n");
+
+        let u = Element::use_href(0, 0).href("#icon").width(10).render(None);
+        assert_eq!(u, "<use x=\"0\" y=\"0\" width=\"10\" href=\"#icon\"/>\n");
     }
 }
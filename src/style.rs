@@ -1,8 +1,7 @@
-use std::collections::HashMap;
+use crate::{Visit, buffer::Buffer, color::Color, diagnostics::url_references, units::Length};
 
-use crate::{Visit, buffer::Buffer, color::Color, units::Length};
-
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Style {
     pub fill: Option<Color>,
     pub fill_opacity: Option<f32>,
@@ -19,17 +18,66 @@ pub struct Style {
 
     pub stroke_width: Option<Length>,
 
-    pub kv: HashMap<String, String>,
+    pub font_family: Option<String>,
+    pub font_size: Option<Length>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub font_variant: Option<FontVariant>,
+    pub font_stretch: Option<FontStretch>,
+
+    pub dominant_baseline: Option<DominantBaseline>,
+    pub alignment_baseline: Option<AlignmentBaseline>,
+    pub baseline_shift: Option<BaselineShift>,
+    pub writing_mode: Option<WritingMode>,
+    pub letter_spacing: Option<Length>,
+    pub word_spacing: Option<Length>,
+    pub text_decoration: Option<TextDecoration>,
+
+    /// custom properties, rendered in insertion order
+    pub kv: Vec<(String, String)>,
+}
+
+impl Style {
+    /// Sets a custom property, the value of an existing property is replaced in place.
+    pub fn insert_kv(&mut self, key: &str, value: String) {
+        match self.kv.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.kv.push((key.to_string(), value)),
+        }
+    }
+
+    /// Removes the properties that only applies to text content elements
+    pub(crate) fn clear_text_properties(&mut self) {
+        self.font_family = None;
+        self.font_size = None;
+        self.font_weight = None;
+        self.font_style = None;
+        self.font_variant = None;
+        self.font_stretch = None;
+        self.dominant_baseline = None;
+        self.alignment_baseline = None;
+        self.baseline_shift = None;
+        self.writing_mode = None;
+        self.letter_spacing = None;
+        self.word_spacing = None;
+        self.text_decoration = None;
+    }
 }
 
 impl Visit for Style {
     fn visit(&self, buffer: &mut Buffer) {
+        // start of every declaration, used to sort them
+        let mut declarations = vec![];
         macro_rules! visit_if_not_none {
             ($ident:ident, $str:literal) => {
+                visit_if_not_none!($ident, $str, Visit::visit);
+            };
+            ($ident:ident, $str:literal, $visit:expr) => {
                 if let Some($ident) = &self.$ident {
-                    let str = format!("{}: ", $str);
-                    buffer.push_str(&str);
-                    $ident.visit(buffer);
+                    declarations.push(buffer.len());
+                    buffer.push_str($str);
+                    buffer.push_str(": ");
+                    $visit($ident, buffer);
                     buffer.push_str("; ");
                 }
             };
@@ -48,6 +96,19 @@ impl Visit for Style {
             stroke_miterlimit: None,
             stroke_opacity: None,
             stroke_width: None,
+            font_family: None,
+            font_size: None,
+            font_weight: None,
+            font_style: None,
+            font_variant: None,
+            font_stretch: None,
+            dominant_baseline: None,
+            alignment_baseline: None,
+            baseline_shift: None,
+            writing_mode: None,
+            letter_spacing: None,
+            word_spacing: None,
+            text_decoration: None,
             kv,
         } = &self
             && kv.is_empty()
@@ -61,15 +122,46 @@ impl Visit for Style {
         visit_if_not_none!(fill_opacity, "fill-opacity");
         visit_if_not_none!(fill_rule, "fill-rule");
         visit_if_not_none!(stroke, "stroke");
-        visit_if_not_none!(stroke_width, "stroke-width");
+        visit_if_not_none!(stroke_width, "stroke-width", Length::visit_with_unit);
         visit_if_not_none!(stroke_linejoin, "stroke-linejoin");
-        visit_if_not_none!(stroke_dasharray, "stroke-dasharray");
-        visit_if_not_none!(stroke_dashoffset, "stroke-dashoffset");
+        visit_if_not_none!(stroke_dasharray, "stroke-dasharray", lengths_with_unit);
+        visit_if_not_none!(
+            stroke_dashoffset,
+            "stroke-dashoffset",
+            Length::visit_with_unit
+        );
         visit_if_not_none!(stroke_linecap, "stroke-linecap");
         visit_if_not_none!(stroke_miterlimit, "stroke-miterlimit");
-        visit_if_not_none!(stroke_opacity, "stroke_opacity");
+        visit_if_not_none!(stroke_opacity, "stroke-opacity");
+        visit_if_not_none!(font_family, "font-family");
+        visit_if_not_none!(font_size, "font-size", Length::visit_with_unit);
+        visit_if_not_none!(font_weight, "font-weight");
+        visit_if_not_none!(font_style, "font-style");
+        visit_if_not_none!(font_variant, "font-variant");
+        visit_if_not_none!(font_stretch, "font-stretch");
+        visit_if_not_none!(dominant_baseline, "dominant-baseline");
+        visit_if_not_none!(alignment_baseline, "alignment-baseline");
+        visit_if_not_none!(
+            baseline_shift,
+            "baseline-shift",
+            BaselineShift::visit_with_unit
+        );
+        visit_if_not_none!(writing_mode, "writing-mode");
+        visit_if_not_none!(letter_spacing, "letter-spacing", Length::visit_with_unit);
+        visit_if_not_none!(word_spacing, "word-spacing", Length::visit_with_unit);
+        visit_if_not_none!(text_decoration, "text-decoration");
         for (k, v) in &self.kv {
-            buffer.push_str(&format!("{}:{}; ", k, v));
+            declarations.push(buffer.len());
+            for target in url_references(v) {
+                buffer.reference(target);
+            }
+            buffer.push_escaped(k);
+            buffer.push(':');
+            buffer.push_escaped(v);
+            buffer.push_str("; ");
+        }
+        if buffer.opts.sort_attributes {
+            buffer.sort_segments(&declarations, |d| d.split(':').next().unwrap_or(d).trim());
         }
 
         buffer.pop(); //remove the last whitespace
@@ -77,7 +169,17 @@ impl Visit for Style {
     }
 }
 
-#[derive(Debug)]
+fn lengths_with_unit(lengths: &[Length], buffer: &mut Buffer) {
+    for (i, length) in lengths.iter().enumerate() {
+        if i > 0 {
+            buffer.push(' ');
+        }
+        length.visit_with_unit(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FillRule {
     NonZero,
     EvenOdd,
@@ -93,7 +195,8 @@ impl Visit for FillRule {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineCap {
     Butt,
     Round,
@@ -111,7 +214,8 @@ impl Visit for LineCap {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineJoin {
     Miter,
     Round,
@@ -128,3 +232,267 @@ impl Visit for LineJoin {
         buffer.push_str(str);
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FontWeight {
+    Normal,
+    Bold,
+    Bolder,
+    Lighter,
+    /// numeric weight between 1 and 1000
+    Weight(u16),
+}
+
+impl Visit for FontWeight {
+    fn visit(&self, buffer: &mut Buffer) {
+        let str = match self {
+            FontWeight::Normal => "normal",
+            FontWeight::Bold => "bold",
+            FontWeight::Bolder => "bolder",
+            FontWeight::Lighter => "lighter",
+            FontWeight::Weight(w) => {
+                debug_assert!((1..=1000).contains(w), "font-weight must be in 1..=1000");
+                return w.visit(buffer);
+            }
+        };
+        buffer.push_str(str);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+impl Visit for FontStyle {
+    fn visit(&self, buffer: &mut Buffer) {
+        let str = match self {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        };
+        buffer.push_str(str);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FontVariant {
+    Normal,
+    SmallCaps,
+}
+
+impl Visit for FontVariant {
+    fn visit(&self, buffer: &mut Buffer) {
+        let str = match self {
+            FontVariant::Normal => "normal",
+            FontVariant::SmallCaps => "small-caps",
+        };
+        buffer.push_str(str);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FontStretch {
+    UltraCondensed,
+    ExtraCondensed,
+    Condensed,
+    SemiCondensed,
+    Normal,
+    SemiExpanded,
+    Expanded,
+    ExtraExpanded,
+    UltraExpanded,
+}
+
+impl Visit for FontStretch {
+    fn visit(&self, buffer: &mut Buffer) {
+        let str = match self {
+            FontStretch::UltraCondensed => "ultra-condensed",
+            FontStretch::ExtraCondensed => "extra-condensed",
+            FontStretch::Condensed => "condensed",
+            FontStretch::SemiCondensed => "semi-condensed",
+            FontStretch::Normal => "normal",
+            FontStretch::SemiExpanded => "semi-expanded",
+            FontStretch::Expanded => "expanded",
+            FontStretch::ExtraExpanded => "extra-expanded",
+            FontStretch::UltraExpanded => "ultra-expanded",
+        };
+        buffer.push_str(str);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DominantBaseline {
+    Auto,
+    TextBottom,
+    Alphabetic,
+    Ideographic,
+    Middle,
+    Central,
+    Mathematical,
+    Hanging,
+    TextTop,
+}
+
+impl Visit for DominantBaseline {
+    fn visit(&self, buffer: &mut Buffer) {
+        let str = match self {
+            DominantBaseline::Auto => "auto",
+            DominantBaseline::TextBottom => "text-bottom",
+            DominantBaseline::Alphabetic => "alphabetic",
+            DominantBaseline::Ideographic => "ideographic",
+            DominantBaseline::Middle => "middle",
+            DominantBaseline::Central => "central",
+            DominantBaseline::Mathematical => "mathematical",
+            DominantBaseline::Hanging => "hanging",
+            DominantBaseline::TextTop => "text-top",
+        };
+        buffer.push_str(str);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlignmentBaseline {
+    Baseline,
+    TextBottom,
+    Alphabetic,
+    Ideographic,
+    Middle,
+    Central,
+    Mathematical,
+    TextTop,
+}
+
+impl Visit for AlignmentBaseline {
+    fn visit(&self, buffer: &mut Buffer) {
+        let str = match self {
+            AlignmentBaseline::Baseline => "baseline",
+            AlignmentBaseline::TextBottom => "text-bottom",
+            AlignmentBaseline::Alphabetic => "alphabetic",
+            AlignmentBaseline::Ideographic => "ideographic",
+            AlignmentBaseline::Middle => "middle",
+            AlignmentBaseline::Central => "central",
+            AlignmentBaseline::Mathematical => "mathematical",
+            AlignmentBaseline::TextTop => "text-top",
+        };
+        buffer.push_str(str);
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BaselineShift {
+    Sub,
+    Super,
+    Length(Length),
+}
+
+impl<T: Into<Length>> From<T> for BaselineShift {
+    fn from(value: T) -> Self {
+        BaselineShift::Length(value.into())
+    }
+}
+
+impl BaselineShift {
+    fn visit_with_unit(&self, buffer: &mut Buffer) {
+        match self {
+            BaselineShift::Length(length) => length.visit_with_unit(buffer),
+            shift => shift.visit(buffer),
+        }
+    }
+}
+
+impl Visit for BaselineShift {
+    fn visit(&self, buffer: &mut Buffer) {
+        match self {
+            BaselineShift::Sub => buffer.push_str("sub"),
+            BaselineShift::Super => buffer.push_str("super"),
+            BaselineShift::Length(length) => length.visit(buffer),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WritingMode {
+    HorizontalTb,
+    VerticalRl,
+    VerticalLr,
+}
+
+impl Visit for WritingMode {
+    fn visit(&self, buffer: &mut Buffer) {
+        let str = match self {
+            WritingMode::HorizontalTb => "horizontal-tb",
+            WritingMode::VerticalRl => "vertical-rl",
+            WritingMode::VerticalLr => "vertical-lr",
+        };
+        buffer.push_str(str);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextDecoration {
+    None,
+    Underline,
+    Overline,
+    LineThrough,
+}
+
+impl Visit for TextDecoration {
+    fn visit(&self, buffer: &mut Buffer) {
+        let str = match self {
+            TextDecoration::None => "none",
+            TextDecoration::Underline => "underline",
+            TextDecoration::Overline => "overline",
+            TextDecoration::LineThrough => "line-through",
+        };
+        buffer.push_str(str);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parent, element::Element, units::Px};
+
+    #[test]
+    fn opacities() {
+        let rendered = Element::rect(0, 0, 1, 1)
+            .fill_opacity(0.5)
+            .stroke_opacity(0.25)
+            .render(None);
+        assert!(
+            rendered.contains(r#"style="fill-opacity: 0.5; stroke-opacity: 0.25;""#),
+            "{rendered}"
+        );
+    }
+
+    #[test]
+    fn lengths_keep_their_unit() {
+        let rendered = Element::svg()
+            .push(
+                Element::text(0, 0)
+                    .font_size(12)
+                    .letter_spacing(Px(1.5))
+                    .stroke_width(2)
+                    .push("a"),
+            )
+            .render(None);
+        assert!(
+            rendered
+                .contains(r#"style="stroke-width: 2px; font-size: 12px; letter-spacing: 1.5px;""#),
+            "{rendered}"
+        );
+        // presentation attributes don't need the unit
+        assert!(rendered.contains(r#"<text x="0" y="0""#), "{rendered}");
+    }
+}
@@ -0,0 +1,276 @@
+// ===== Gallery ================================================================
+//
+// Writes many svgs to a single html page to look them over side by side. The page works
+// offline, it has no scripts, fonts or stylesheets other than its own. Every svg is embedded as
+// an image so the ids and css of one svg can't affect another, the source is shown below it.
+
+use std::{fmt::Write, fs, io, path::Path};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::{
+    buffer::Buffer, element::Element, marker_traits::ElementKind, shapes::svg::Svg, visit::Visit,
+};
+
+/// The background behind the svgs, it can be changed on the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Background {
+    #[default]
+    Checkerboard,
+    Dark,
+    Light,
+}
+
+impl Background {
+    const ALL: [Background; 3] = [
+        Background::Checkerboard,
+        Background::Dark,
+        Background::Light,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Background::Checkerboard => "checkerboard",
+            Background::Dark => "dark",
+            Background::Light => "light",
+        }
+    }
+}
+
+/// A page of svgs in a grid with their captions, sizes and sources.
+/// ```
+/// # use svg_maker::{Parent, color::Color, element::Element, tester::{Background, Gallery}};
+/// let html = Gallery::new("Charts")
+///     .background(Background::Dark)
+///     .push("bar", &Element::svg().push(Element::rect(0, 0, 10, 10).fill(Color::Red)))
+///     .push("empty", &Element::svg())
+///     .render();
+/// assert!(html.contains("<figcaption>bar</figcaption>"));
+/// assert!(!html.contains("<script"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Gallery {
+    title: String,
+    background: Background,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone)]
+struct Item {
+    caption: String,
+    /// the svg as rendered by default, shown as the source
+    source: String,
+    size: String,
+}
+
+impl Gallery {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The background that is selected when the page is opened
+    pub fn background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn push(mut self, caption: &str, svg: &Element<Svg>) -> Self {
+        let source = svg.render(None);
+        let mut size = match (&svg.kind.w, &svg.kind.h) {
+            (Some(w), Some(h)) => format!("{} × {}", w.visit_return(), h.visit_return()),
+            (Some(w), None) => format!("width {}", w.visit_return()),
+            (None, Some(h)) => format!("height {}", h.visit_return()),
+            (None, None) => "no size".to_string(),
+        };
+        if let Some(viewbox) = &svg.kind.viewbox {
+            let _ = write!(size, ", viewBox {}", viewbox.visit_return());
+        }
+        let _ = write!(size, ", {} bytes", source.len());
+        self.items.push(Item {
+            caption: caption.to_string(),
+            source,
+            size,
+        });
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Renders the page
+    pub fn render(&self) -> String {
+        let mut b = Buffer::with_capacity(1000);
+        b.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"UTF-8\">\n");
+        b.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+        b.push_str("<title>");
+        b.push_escaped(&self.title);
+        b.push_str("</title>\n<style>");
+        b.push_str(CSS);
+        b.push_str("</style>\n</head>\n<body>\n");
+
+        // the backgrounds are radio buttons that are siblings of the grid, so they can be
+        // switched with css alone
+        for background in Background::ALL {
+            let name = background.name();
+            b.push_fmt(format_args!(
+                "<input type=\"radio\" name=\"background\" id=\"{name}\" class=\"background\"{}>\n",
+                if background == self.background {
+                    " checked"
+                } else {
+                    ""
+                }
+            ));
+        }
+        b.push_str("<header>\n<h1>");
+        b.push_escaped(&self.title);
+        b.push_str("</h1>\n<nav>");
+        for background in Background::ALL {
+            let name = background.name();
+            b.push_fmt(format_args!("<label for=\"{name}\">{name}</label>"));
+        }
+        b.push_str("</nav>\n</header>\n<main>\n");
+
+        for item in &self.items {
+            let image = STANDARD.encode(&item.source);
+            b.push_str("<figure>\n<div class=\"preview\"><img alt=\"");
+            b.push_escaped(&item.caption);
+            b.push_fmt(format_args!(
+                "\" src=\"data:image/svg+xml;base64,{image}\"></div>\n"
+            ));
+            b.push_str("<figcaption>");
+            b.push_escaped(&item.caption);
+            b.push_str("</figcaption>\n<p class=\"size\">");
+            b.push_escaped(&item.size);
+            b.push_str("</p>\n<details><summary>source</summary><pre><code>");
+            b.push_escaped(&item.source);
+            b.push_str("</code></pre></details>\n</figure>\n");
+        }
+        b.push_str("</main>\n</body>\n</html>\n");
+        b.str().to_string()
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.render())
+    }
+}
+
+/// Writes the svgs to a gallery page, the captions are the ids of the svgs or their position.
+pub fn run(svgs: &[Element<Svg>], path: impl AsRef<Path>) -> io::Result<()> {
+    let gallery = svgs
+        .iter()
+        .enumerate()
+        .fold(Gallery::new(Svg::TAG), |gallery, (i, svg)| {
+            let caption = svg.id.clone().unwrap_or_else(|| format!("#{}", i + 1));
+            gallery.push(&caption, svg)
+        });
+    gallery.write(path)
+}
+
+const CSS: &str = r#"
+:root {
+    color-scheme: light dark;
+    font-family: system-ui, sans-serif;
+    background: #2b2b2b;
+    color: #eee;
+}
+body { margin: 0; padding: 1rem; }
+header { display: flex; align-items: baseline; gap: 1rem; }
+h1 { font-size: 1.25rem; margin: 0 0 1rem; }
+.background { display: none; }
+nav label {
+    padding: 0.2rem 0.6rem;
+    border: 1px solid #666;
+    border-radius: 4px;
+    cursor: pointer;
+}
+#checkerboard:checked ~ header label[for="checkerboard"],
+#dark:checked ~ header label[for="dark"],
+#light:checked ~ header label[for="light"] { background: #555; }
+main {
+    display: grid;
+    grid-template-columns: repeat(auto-fill, minmax(260px, 1fr));
+    gap: 1rem;
+}
+figure { margin: 0; min-width: 0; }
+.preview {
+    display: flex;
+    align-items: center;
+    justify-content: center;
+    height: 240px;
+    border: 1px solid #555;
+    background: repeating-conic-gradient(#ccc 0 25%, #fff 0 50%) 0 0 / 16px 16px;
+}
+#dark:checked ~ main .preview { background: #111; }
+#light:checked ~ main .preview { background: #fff; }
+.preview img { max-width: 100%; max-height: 100%; }
+figcaption { margin-top: 0.4rem; font-weight: 600; overflow-wrap: anywhere; }
+.size { margin: 0.2rem 0; font-size: 0.8rem; color: #aaa; }
+pre {
+    max-height: 300px;
+    overflow: auto;
+    padding: 0.5rem;
+    font-size: 0.75rem;
+    background: #1e1e1e;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::{Parent, color::Color};
+
+    use super::*;
+
+    #[test]
+    fn gallery() {
+        let chart = Element::svg()
+            .id("chart")
+            .size(40, 20)
+            .viewbox(0, 0, 4, 2)
+            .css("rect { fill: red }")
+            .push(Element::rect(0, 0, 1, 1).id("bar").fill(Color::Red));
+        let html = Gallery::new("Charts & <graphs>")
+            .background(Background::Light)
+            .push("a < b", &chart)
+            .push("empty", &Element::svg())
+            .render();
+
+        assert!(html.contains("<title>Charts &amp; &lt;graphs&gt;</title>"));
+        assert!(html.contains("<figcaption>a &lt; b</figcaption>"));
+        assert_eq!(html.matches("<figure>").count(), 2);
+        assert_eq!(html.matches("<details>").count(), 2);
+        assert!(html.contains(r#"id="light" class="background" checked>"#));
+        assert!(html.contains(r#"id="dark" class="background">"#));
+        assert!(html.contains("40 × 20, viewBox 0 0 4 2, "));
+
+        // the source is escaped and the svg is embedded as it is rendered
+        let source = chart.render(None);
+        assert!(html.contains("&lt;rect id=&quot;bar&quot;"));
+        let image = STANDARD.encode(&source);
+        assert!(html.contains(&format!("src=\"data:image/svg+xml;base64,{image}\"")));
+
+        // nothing is loaded from elsewhere
+        for external in ["<script", "<link", "src=\"http", "@import", "url(http"] {
+            assert!(!html.contains(external), "{external}");
+        }
+    }
+
+    #[test]
+    fn run_writes_a_page() {
+        let path =
+            std::env::temp_dir().join(format!("svg_maker_gallery_{}.html", std::process::id()));
+        let svgs = [Element::svg().id("first"), Element::svg()];
+        run(&svgs, &path).unwrap();
+        let html = fs::read_to_string(&path).unwrap();
+        assert!(html.contains("<figcaption>first</figcaption>"));
+        assert!(html.contains("<figcaption>#2</figcaption>"));
+        fs::remove_file(&path).unwrap();
+    }
+}
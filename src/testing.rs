@@ -0,0 +1,243 @@
+// ===== Snapshot testing =======================================================
+//
+// Rendered elements are compared with snapshots stored in `tests/snapshots`, a mismatch is
+// reported with a structural diff so formatting changes don't fail the tests. New snapshots are
+// written on the first run, except on CI where a missing snapshot fails the test.
+
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Options,
+    diff::{Change, ChangeKind, DEFAULT_TOLERANCE, Diff, diff_rendered},
+    element::Element,
+    marker_traits::ElementKind,
+    visit::Visit,
+};
+
+/// Set to update the snapshots that don't match instead of failing the tests
+pub const UPDATE_ENV: &str = "SVG_MAKER_UPDATE_SNAPSHOTS";
+
+/// Compares the element with the snapshot `tests/snapshots/{name}.svg` of the crate that calls
+/// the macro and panics with the differences if they don't match. Run the tests with
+/// `SVG_MAKER_UPDATE_SNAPSHOTS=1` to update the snapshots.
+/// ```no_run
+/// # use svg_maker::{Parent, assert_svg_snapshot, element::Element};
+/// let chart = Element::svg().push(Element::rect(0, 0, 10, 10));
+/// assert_svg_snapshot!("chart", chart);
+/// ```
+#[macro_export]
+macro_rules! assert_svg_snapshot {
+    ($name:expr, $element:expr) => {
+        $crate::testing::assert_snapshot(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests")
+                .join("snapshots"),
+            $name,
+            &$element,
+        )
+    };
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The snapshot doesn't exist and can't be created on CI
+    Missing(PathBuf),
+    Mismatch {
+        path: PathBuf,
+        diff: Diff,
+    },
+    Io(PathBuf, io::Error),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Missing(path) => {
+                write!(f, "snapshot {} is missing", path.display())
+            }
+            SnapshotError::Mismatch { path, diff } => {
+                writeln!(f, "snapshot {} doesn't match:", path.display())?;
+                write!(f, "{diff}")?;
+                write!(f, "run the tests with {UPDATE_ENV}=1 to update it")
+            }
+            SnapshotError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// What to do when a snapshot is missing or doesn't match
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    /// write missing snapshots
+    New,
+    /// fail on missing snapshots
+    Ci,
+    /// overwrite the snapshots
+    Update,
+}
+
+impl Mode {
+    fn from_env() -> Self {
+        let set = |var| std::env::var_os(var).is_some_and(|v| !v.is_empty() && v != "0");
+        if set(UPDATE_ENV) {
+            Mode::Update
+        } else if set("CI") {
+            Mode::Ci
+        } else {
+            Mode::New
+        }
+    }
+}
+
+/// The form the snapshots are stored in: the default options with sorted attributes.
+pub fn canonicalize<T: ElementKind + Visit>(element: &Element<T>) -> String {
+    let opts = Options {
+        sort_attributes: true,
+        ..Default::default()
+    };
+    element.render(Some(opts))
+}
+
+/// Compares the element with the snapshot `{dir}/{name}.svg`, see [`assert_svg_snapshot`].
+pub fn check_snapshot<T: ElementKind + Visit>(
+    dir: impl AsRef<Path>,
+    name: &str,
+    element: &Element<T>,
+) -> Result<(), SnapshotError> {
+    check(dir.as_ref(), name, &canonicalize(element), Mode::from_env())
+}
+
+/// Panics if the element doesn't match the snapshot, used by [`assert_svg_snapshot`].
+#[track_caller]
+pub fn assert_snapshot<T: ElementKind + Visit>(
+    dir: impl AsRef<Path>,
+    name: &str,
+    element: &Element<T>,
+) {
+    if let Err(err) = check_snapshot(dir, name, element) {
+        panic!("{err}");
+    }
+}
+
+fn check(dir: &Path, name: &str, rendered: &str, mode: Mode) -> Result<(), SnapshotError> {
+    let path = dir.join(format!("{name}.svg"));
+    let write = || {
+        fs::create_dir_all(dir)
+            .and_then(|_| fs::write(&path, rendered))
+            .map_err(|err| SnapshotError::Io(path.clone(), err))
+    };
+
+    let snapshot = match fs::read_to_string(&path) {
+        Ok(snapshot) => snapshot,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return match mode {
+                Mode::Ci => Err(SnapshotError::Missing(path)),
+                Mode::New | Mode::Update => write(),
+            };
+        }
+        Err(err) => return Err(SnapshotError::Io(path, err)),
+    };
+    if snapshot == rendered {
+        return Ok(());
+    }
+    if mode == Mode::Update {
+        return write();
+    }
+    if without_formatting(&snapshot) == without_formatting(rendered) {
+        return Ok(());
+    }
+    let mut diff = diff_rendered(&snapshot, rendered, DEFAULT_TOLERANCE);
+    // without a tolerance the numbers show up, if nothing does the trees differ in a way the
+    // diff can't show, like text that moved past a child element
+    if diff.is_empty() && diff_rendered(&snapshot, rendered, 0.).is_empty() {
+        let root = rendered.trim_start().trim_start_matches('<');
+        let tag = root.split([' ', '>', '/', '\n']).next().unwrap_or_default();
+        diff.changes.push(Change {
+            path: tag.to_string(),
+            id: None,
+            kind: ChangeKind::Text {
+                old: snapshot.clone(),
+                new: rendered.to_string(),
+            },
+        });
+    }
+    if diff.is_empty() {
+        Ok(())
+    } else {
+        Err(SnapshotError::Mismatch { path, diff })
+    }
+}
+
+/// The rendered tree without the line breaks and tabs of the indentation
+fn without_formatting(rendered: &str) -> String {
+    rendered.replace(['\n', '\r', '\t'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parent;
+
+    use super::*;
+
+    #[test]
+    fn snapshots() {
+        let dir = std::env::temp_dir().join(format!("svg_maker_snapshots_{}", std::process::id()));
+        let chart = |height: u32| {
+            Element::svg()
+                .push(Element::rect(0, 0, 10, height).id("bar").class("bar"))
+                .push(Element::text(0, 0).push("label".to_string()))
+        };
+        let check = |height, mode| check(&dir, "chart", &canonicalize(&chart(height)), mode);
+
+        assert!(matches!(
+            check(10, Mode::Ci),
+            Err(SnapshotError::Missing(_))
+        ));
+        check(10, Mode::New).unwrap();
+        let snapshot = fs::read_to_string(dir.join("chart.svg")).unwrap();
+        assert!(snapshot.contains(r#"<rect class="bar" height="10" id="bar" width="10""#));
+        check(10, Mode::Ci).unwrap();
+
+        // formatting is ignored
+        let unformatted = snapshot.replace(['\n', '\t'], "");
+        fs::write(dir.join("chart.svg"), unformatted).unwrap();
+        check(10, Mode::Ci).unwrap();
+
+        let err = check(12, Mode::New).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("~ svg > rect:nth-child(1) #bar: height \"10\" -> \"12\"")
+        );
+        check(12, Mode::Update).unwrap();
+        check(12, Mode::Ci).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn mismatches_the_diff_hides() {
+        let dir = std::env::temp_dir().join(format!("svg_maker_mismatch_{}", std::process::id()));
+        let snapshot = r#"<svg><text>a<tspan>b</tspan></text><rect width="1"/></svg>"#;
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("text.svg"), snapshot).unwrap();
+        let check = |rendered: &str| check(&dir, "text", rendered, Mode::Ci);
+
+        // numbers within the tolerance match
+        check(&snapshot.replace(r#""1""#, r#""1.0000001""#)).unwrap();
+        // whitespace in text doesn't
+        let err = check(&snapshot.replace(">a<", "> a<")).unwrap_err();
+        assert!(err.to_string().contains(r#"text "a" -> " a""#));
+        // text that moved past the tspan isn't in the diff, the whole tree is reported
+        let moved = r#"<svg><text><tspan>b</tspan>a</text><rect width="1"/></svg>"#;
+        let err = check(moved).unwrap_err();
+        assert!(err.to_string().contains("~ svg: text "));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+// ===== Text layout ===========================================================
+//
+// SVG has no line wrapping, so a block of text is broken into lines up front and each line is
+// emitted as a <tspan> that resets x and moves down one line height.
+
+use unicode_linebreak::{BreakOpportunity, linebreaks};
+
+use crate::{
+    Parent,
+    element::Element,
+    font::Font,
+    shapes::{text::Text, tspan::Tspan},
+    units::{Length, Px, TextAnchor},
+};
+
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Layout options for a block of wrapped text.
+/// ```
+/// # use svg_maker::{font::Font, text_layout::TextBlock, units::TextAnchor};
+/// let title = TextBlock::new(120., 16.)
+///     .anchor(TextAnchor::Middle)
+///     .max_lines(2)
+///     .layout("A very long chart title that needs to wrap", &Font::new("Inter", 12.));
+/// assert!(title.len() <= 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextBlock {
+    max_width: f64,
+    line_height: f64,
+    anchor: TextAnchor,
+    max_lines: Option<usize>,
+}
+
+impl TextBlock {
+    pub fn new(max_width: f64, line_height: f64) -> Self {
+        Self {
+            max_width,
+            line_height,
+            anchor: TextAnchor::Start,
+            max_lines: None,
+        }
+    }
+
+    /// Aligns the lines inside the block, the x position of the lines is set to the start,
+    /// middle or end of `max_width`.
+    pub fn anchor(mut self, anchor: TextAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Truncates the text after `lines` lines, the last line ends with an ellipsis.
+    pub fn max_lines(mut self, lines: usize) -> Self {
+        self.max_lines = Some(lines);
+        self
+    }
+
+    /// Breaks the text into lines and returns a text element with one tspan per line.
+    /// The baseline of the first line is at y = 0.
+    pub fn layout(&self, text: &str, font: &Font) -> Element<Text> {
+        let x = match self.anchor {
+            TextAnchor::Start => 0.,
+            TextAnchor::Middle => self.max_width / 2.,
+            TextAnchor::End => self.max_width,
+        };
+
+        // an empty tspan doesn't move the baseline, blank lines are added to the next dy
+        let mut dy = 0.;
+        let mut tspans = vec![];
+        for (i, line) in self.lines(text, font).into_iter().enumerate() {
+            if i > 0 {
+                dy += self.line_height;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            tspans.push(
+                Element::<Tspan>::tspan_positioned([Px(x)], Vec::<Length>::new())
+                    .dy([Px(dy)])
+                    .push(line),
+            );
+            dy = 0.;
+        }
+
+        Element::text(Px(x), 0)
+            .text_anchor(self.anchor)
+            .font_family(&font.family)
+            .font_size(font.size)
+            .push_iter(tspans)
+    }
+
+    /// Breaks the text on unicode line break opportunities so that every line fits within
+    /// `max_width`. A single word that is wider than `max_width` gets a line of its own.
+    pub fn lines(&self, text: &str, font: &Font) -> Vec<String> {
+        let mut lines = vec![];
+        let mut line = String::new();
+        // the width of the line with its trailing whitespace, only new segments are measured
+        let mut width = 0.;
+        let mut start = 0;
+
+        for (end, opportunity) in linebreaks(text) {
+            let segment = &text[start..end];
+            start = end;
+
+            let word = segment.trim_end();
+            if !line.is_empty()
+                && !word.is_empty()
+                && width + font.text_width(word) > self.max_width
+            {
+                lines.push(line.trim_end().to_string());
+                line.clear();
+                width = 0.;
+            }
+            line.push_str(segment);
+            width += font.text_width(segment);
+
+            if opportunity == BreakOpportunity::Mandatory {
+                lines.push(line.trim_end().to_string());
+                line.clear();
+                width = 0.;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.trim_end().to_string());
+        }
+
+        if let Some(max) = self.max_lines
+            && lines.len() > max
+        {
+            lines.truncate(max);
+            if let Some(last) = lines.last_mut() {
+                *last = self.ellipsize(last, font);
+            }
+        }
+
+        lines
+    }
+
+    /// removes characters from the end of the line until it fits with an ellipsis appended.
+    fn ellipsize(&self, line: &str, font: &Font) -> String {
+        let mut line = line.trim_end().to_string();
+        while !line.is_empty() && font.text_width(&(line.clone() + ELLIPSIS)) > self.max_width {
+            line.pop();
+            line.truncate(line.trim_end().len());
+        }
+        line + ELLIPSIS
+    }
+}
+
+impl Element<Text> {
+    /// Wraps the text into lines no wider than `max_width`, see [`TextBlock`] for alignment and
+    /// truncation.
+    pub fn text_block(text: &str, max_width: f64, line_height: f64, font: &Font) -> Self {
+        TextBlock::new(max_width, line_height).layout(text, font)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Options;
+
+    use super::*;
+
+    fn font() -> Font {
+        Font::new("Inter", 10.)
+    }
+
+    #[test]
+    fn wraps_on_break_opportunities() {
+        let block = TextBlock::new(60., 12.);
+        let lines = block.lines("aaaa bbbb cccc dddd", &font());
+        assert_eq!(lines, vec!["aaaa bbbb", "cccc dddd"]);
+        assert!(lines.iter().all(|l| font().text_width(l) <= 60.));
+    }
+
+    #[test]
+    fn mandatory_breaks() {
+        let block = TextBlock::new(1000., 12.);
+        let lines = block.lines("first\nsecond", &font());
+        assert_eq!(lines, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn blank_lines() {
+        let block = TextBlock::new(1000., 12.);
+        assert_eq!(block.lines("a\n\nb", &font()), vec!["a", "", "b"]);
+
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+        let text = block.layout("\na\n\nb", &font()).render(Some(opts));
+        assert!(
+            text.contains(r#"<tspan x="0" dy="12">a</tspan><tspan x="0" dy="24">b</tspan>"#),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn long_word_overflows_on_its_own_line() {
+        let block = TextBlock::new(20., 12.);
+        let lines = block.lines("a aaaaaaaaaa b", &font());
+        assert_eq!(lines, vec!["a", "aaaaaaaaaa", "b"]);
+    }
+
+    #[test]
+    fn ellipsis_after_max_lines() {
+        let block = TextBlock::new(60., 12.).max_lines(1);
+        let lines = block.lines("aaaa bbbb cccc dddd", &font());
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with(ELLIPSIS));
+        assert!(font().text_width(&lines[0]) <= 60.);
+    }
+
+    #[test]
+    fn text_block() {
+        let mut opts = Options::default();
+        opts.optimizations.remove_newline = true;
+        opts.optimizations.remove_indent = true;
+
+        let text = TextBlock::new(60., 12.)
+            .anchor(TextAnchor::Middle)
+            .layout("aaaa bbbb cccc", &font())
+            .render(Some(opts));
+        let expected = r#"<text x="30" y="0" text-anchor="middle" style="font-family: Inter; font-size: 10px;"><tspan x="30" dy="0">aaaa bbbb</tspan><tspan x="30" dy="12">cccc</tspan></text>"#;
+        assert_eq!(text, expected);
+    }
+}
@@ -1,8 +1,6 @@
 use std::{
     collections::VecDeque,
-    fmt::Display,
     ops::{Add, Sub},
-    rc::Rc,
 };
 
 use derive_more::Display;
@@ -10,7 +8,8 @@ use num_traits::Num;
 
 use crate::{buffer::Buffer, visit::Visit};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coord(pub XCoord, pub YCoord);
 
 impl Visit for Coord {
@@ -37,7 +36,8 @@ impl From<[f64; 2]> for Coord {
     }
 }
 
-#[derive(Display, Debug, Default, PartialEq)]
+#[derive(Clone, Display, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct XCoord(pub f64);
 
 impl<T: Num + Into<f64>> From<T> for XCoord {
@@ -48,11 +48,12 @@ impl<T: Num + Into<f64>> From<T> for XCoord {
 
 impl Visit for XCoord {
     fn visit(&self, buffer: &mut Buffer) {
-        buffer.push_str(&format!("{}", Truncated(self.0)));
+        buffer.push_number(self.0);
     }
 }
 
-#[derive(Display, Debug, Default, PartialEq)]
+#[derive(Clone, Display, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct YCoord(pub f64);
 
 impl<T: Num + Into<f64>> From<T> for YCoord {
@@ -72,11 +73,12 @@ impl Visit for YCoord {
         } else {
             self.0
         };
-        buffer.push_str(&format!("{}", Truncated(value)));
+        buffer.push_number(value);
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CubicArgs {
     pub p1: Coord,
     pub p2: Coord,
@@ -103,8 +105,89 @@ impl Visit for CubicArgs {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadraticArgs {
+    pub p1: Coord,
+    pub end: Coord,
+}
+
+impl From<[f64; 4]> for QuadraticArgs {
+    fn from(value: [f64; 4]) -> Self {
+        Self {
+            p1: Coord::from((value[0], value[1])),
+            end: Coord::from((value[2], value[3])),
+        }
+    }
+}
+
+impl Visit for QuadraticArgs {
+    fn visit(&self, buffer: &mut Buffer) {
+        self.p1.visit(buffer);
+        buffer.push_space();
+        self.end.visit(buffer);
+    }
+}
+
+/// Arguments of the smooth cubic bezier command, the first control point is the reflection of
+/// the second control point of the previous command.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmoothCubicArgs {
+    pub p2: Coord,
+    pub end: Coord,
+}
+
+impl From<[f64; 4]> for SmoothCubicArgs {
+    fn from(value: [f64; 4]) -> Self {
+        Self {
+            p2: Coord::from((value[0], value[1])),
+            end: Coord::from((value[2], value[3])),
+        }
+    }
+}
+
+impl Visit for SmoothCubicArgs {
+    fn visit(&self, buffer: &mut Buffer) {
+        self.p2.visit(buffer);
+        buffer.push_space();
+        self.end.visit(buffer);
+    }
+}
+
+/// Arguments of the elliptical arc command
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArcArgs {
+    pub rx: f64,
+    pub ry: f64,
+    /// rotation of the x axis of the ellipse in degrees
+    pub x_axis_rotation: f64,
+    pub large_arc: bool,
+    pub sweep: bool,
+    pub end: Coord,
+}
+
+impl Visit for ArcArgs {
+    fn visit(&self, buffer: &mut Buffer) {
+        let flag = |f: bool| if f { "1" } else { "0" };
+        buffer.push_number(self.rx);
+        buffer.push(',');
+        buffer.push_number(self.ry);
+        buffer.push_space();
+        buffer.push_number(self.x_axis_rotation);
+        buffer.push_space();
+        buffer.push_str(flag(self.large_arc));
+        buffer.push(',');
+        buffer.push_str(flag(self.sweep));
+        buffer.push_space();
+        self.end.visit(buffer);
+    }
+}
+
 // NOTE: the struct needs to have the same name as the enum varuant for the macro to work
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Length {
     Percent(Percent),
     Px(Px),
@@ -144,20 +227,32 @@ impl Default for Length {
     }
 }
 
-impl Visit for Length {
-    fn visit(&self, buffer: &mut Buffer) {
+impl Length {
+    /// Visits the length with the px unit, a length without unit is only valid in a
+    /// presentation attribute, not in css.
+    pub(crate) fn visit_with_unit(&self, buffer: &mut Buffer) {
+        self.push(buffer, true);
+    }
+
+    fn push(&self, buffer: &mut Buffer, unit: bool) {
         match self {
             Length::Percent(percent) => percent.visit(buffer),
-            Length::Px(px) => px.visit(buffer),
+            Length::Px(px) => px.push(buffer, unit),
             Length::Expr(expr) => {
                 buffer.push_str("calc(");
-                expr.visit(buffer);
+                expr.push(buffer, unit);
                 buffer.push(')');
             }
         }
     }
 }
 
+impl Visit for Length {
+    fn visit(&self, buffer: &mut Buffer) {
+        self.push(buffer, !buffer.opts.optimizations.remove_unit_for_px);
+    }
+}
+
 impl From<Percent> for Length {
     fn from(value: Percent) -> Self {
         Self::Percent(value)
@@ -211,7 +306,7 @@ impl<T: Into<Length>> Add<T> for Length {
                 lhs.push_rhs(
                     "+",
                     Expr {
-                        lhs: Rc::new(rhs),
+                        lhs: Box::new(rhs),
                         op: None,
                         rhs: None,
                     },
@@ -219,10 +314,10 @@ impl<T: Into<Length>> Add<T> for Length {
                 Length::Expr(lhs)
             }
             (lhs, rhs) => Length::Expr(Expr {
-                lhs: Rc::new(lhs),
+                lhs: Box::new(lhs),
                 op: Some("+".to_string()),
                 rhs: Some(Box::new(Expr {
-                    lhs: Rc::new(rhs),
+                    lhs: Box::new(rhs),
                     op: None,
                     rhs: None,
                 })),
@@ -232,16 +327,18 @@ impl<T: Into<Length>> Add<T> for Length {
 }
 
 #[derive(Display, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Percent(pub u32);
 
 impl Visit for Percent {
     fn visit(&self, buffer: &mut Buffer) {
-        buffer.push_str(&self.0.to_string());
+        self.0.visit(buffer);
         buffer.push('%');
     }
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Px(pub f64);
 
 impl<T: Num + Into<f64>> From<T> for Px {
@@ -250,18 +347,25 @@ impl<T: Num + Into<f64>> From<T> for Px {
     }
 }
 
-impl Visit for Px {
-    fn visit(&self, buffer: &mut Buffer) {
-        buffer.push_str(&self.0.to_string());
-        if !buffer.opts.optimizations.remove_unit_for_px {
+impl Px {
+    fn push(&self, buffer: &mut Buffer, unit: bool) {
+        buffer.push_number(self.0);
+        if unit {
             buffer.push_str("px");
         }
     }
 }
 
+impl Visit for Px {
+    fn visit(&self, buffer: &mut Buffer) {
+        self.push(buffer, !buffer.opts.optimizations.remove_unit_for_px);
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Expr {
-    lhs: Rc<Length>,
+    lhs: Box<Length>,
     op: Option<String>,
     rhs: Option<Box<Expr>>,
 }
@@ -286,18 +390,24 @@ impl Expr {
     }
 }
 
-impl Visit for Expr {
-    fn visit(&self, buffer: &mut Buffer) {
-        self.lhs.visit(buffer);
+impl Expr {
+    fn push(&self, buffer: &mut Buffer, unit: bool) {
+        self.lhs.push(buffer, unit);
         if let Some(op) = &self.op
             && let Some(rhs) = &self.rhs
         {
             buffer.push_str(op);
-            rhs.visit(buffer);
+            rhs.push(buffer, unit);
         }
     }
 }
 
+impl Visit for Expr {
+    fn visit(&self, buffer: &mut Buffer) {
+        self.push(buffer, !buffer.opts.optimizations.remove_unit_for_px);
+    }
+}
+
 macro_rules! impl_ops_for_lenght_units {
     ($($ident:ident),+) => {
         $(impl<T: Into<Length>> std::ops::Add<T> for $ident {
@@ -344,17 +454,14 @@ impl<T: Num + Into<f64>> From<T> for Angle {
 
 impl Visit for Angle {
     fn visit(&self, buffer: &mut Buffer) {
-        match self {
-            Angle::Deg(v) => {
-                if buffer.opts.optimizations.remove_unit_for_deg {
-                    buffer.push_str(&format!("{}", v))
-                } else {
-                    buffer.push_str(&format!("{}deg", v))
-                }
-            }
-            Angle::Grad(v) => buffer.push_str(&format!("{}grad", v)),
-            Angle::Rad(v) => buffer.push_str(&format!("{}rad", v)),
+        let (v, unit) = match self {
+            Angle::Deg(v) if buffer.opts.optimizations.remove_unit_for_deg => (v, ""),
+            Angle::Deg(v) => (v, "deg"),
+            Angle::Grad(v) => (v, "grad"),
+            Angle::Rad(v) => (v, "rad"),
         };
+        buffer.push_number(*v);
+        buffer.push_str(unit);
     }
 }
 
@@ -384,16 +491,19 @@ impl Visit for Time {
             t = self.to_seconds_if_shorter();
         };
 
-        match t {
-            Time::MilliSeconds(ms) => buffer.push_str(&format!("{}ms", ms)),
-            Time::Seconds(s) => buffer.push_str(&format!("{}s", s)),
-        }
+        let (v, unit) = match t {
+            Time::MilliSeconds(ms) => (ms, "ms"),
+            Time::Seconds(s) => (s, "s"),
+        };
+        buffer.push_number(v);
+        buffer.push_str(unit);
     }
 }
 
 // ====== ALignAspectRatio ====================================================
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignAspectRatio {
     None,
     XMinYMin,
@@ -427,7 +537,8 @@ impl Visit for AlignAspectRatio {
 
 // ====== MeetOrSlice =========================================================
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MeetOrSlice {
     Meet,
     Slice,
@@ -443,7 +554,8 @@ impl Visit for MeetOrSlice {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextAnchor {
     Start,
     Middle,
@@ -461,18 +573,6 @@ impl Visit for TextAnchor {
     }
 }
 
-// ===== Truncated ============================================================
-
-struct Truncated(f64);
-
-impl Display for Truncated {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        const PRECISION: f64 = 1e3;
-        let rounded = (self.0 * PRECISION).round() / PRECISION;
-        write!(f, "{}", rounded)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     // use super::*;
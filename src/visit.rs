@@ -27,15 +27,16 @@ pub trait Visit {
     }
 }
 
+/// Strings are escaped, use [`Raw`](crate::Raw) for markup that should be written as is.
 impl Visit for String {
     fn visit(&self, buffer: &mut Buffer) {
-        buffer.push_str(self);
+        buffer.push_escaped(self);
     }
 }
 
 impl Visit for &str {
     fn visit(&self, buffer: &mut Buffer) {
-        buffer.push_str(self);
+        buffer.push_escaped(self);
     }
 }
 
@@ -56,11 +57,23 @@ macro_rules! impl_visit {
         $(
             impl Visit for $t {
                 fn visit(&self, buffer: &mut Buffer) {
-                    buffer.push_str(&self.to_string());
+                    buffer.push_fmt(format_args!("{self}"));
                 }
             }
         )*
     };
 }
 
-impl_visit!(f64, f32, u64, u32, u16, u8, i64, i32, i16, i8, usize, isize);
+impl Visit for f64 {
+    fn visit(&self, buffer: &mut Buffer) {
+        buffer.push_number(*self);
+    }
+}
+
+impl Visit for f32 {
+    fn visit(&self, buffer: &mut Buffer) {
+        buffer.push_f32(*self);
+    }
+}
+
+impl_visit!(u64, u32, u16, u8, i64, i32, i16, i8, usize, isize);
@@ -7,3 +7,18 @@ fn testin() {
     eprintln!("{s}");
     assert!(s.contains("<svg"))
 }
+
+#[test]
+fn bar_chart_snapshot() {
+    use svg_maker::{Parent, assert_svg_snapshot, color::Color};
+
+    let bars = [3, 7, 5].into_iter().enumerate().map(|(i, value)| {
+        Element::rect(i as u32 * 20, 100 - value * 10, 15, value * 10)
+            .id(&format!("bar-{i}"))
+            .fill(Color::Hex("#4e79a7".to_string()))
+    });
+    let chart = Element::svg()
+        .push(Element::group().id("bars").push_iter(bars))
+        .push(Element::text(0, 110).push("Revenue".to_string()));
+    assert_svg_snapshot!("bar_chart", chart);
+}